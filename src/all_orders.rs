@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::TopologicalSort;
+
+/// An iterator over every distinct topological order of the items of a `TopologicalSort`.
+///
+/// The orders are produced lazily by backtracking over the items which are ready at each step,
+/// trying them in insertion order, so they always come out in the same sequence. Nothing is
+/// returned if the items cannot be sorted because of cyclic dependencies.
+///
+/// This `struct` is created by [`TopologicalSort::all_orders`].
+///
+/// [`TopologicalSort::all_orders`]: struct.TopologicalSort.html#method.all_orders
+pub struct AllOrders<'a, T> {
+    // Items by insertion order, with the positions of the items depending on them.
+    items: Vec<&'a T>,
+    succ: Vec<Vec<usize>>,
+    num_prec: Vec<usize>,
+    path: Vec<usize>,
+    // For each step of `path`, the items which were ready and how many of them were tried.
+    stack: Vec<(Vec<usize>, usize)>,
+    empty: bool,
+}
+
+impl<'a, T: Hash + Eq + Clone> AllOrders<'a, T> {
+    fn new(ts: &'a TopologicalSort<T>) -> AllOrders<'a, T> {
+        let mut items = ts.iter().collect::<Vec<_>>();
+        if items.len() < ts.len() {
+            items.clear();
+        }
+        items.sort_by_key(|&item| ts.top[item].seq);
+        let index = items
+            .iter()
+            .enumerate()
+            .map(|(i, &item)| (item, i))
+            .collect::<HashMap<_, _>>();
+
+        let mut succ = Vec::with_capacity(items.len());
+        let mut num_prec = Vec::with_capacity(items.len());
+        for &item in &items {
+            let mut s = ts.top[item]
+                .succ
+                .iter()
+                .map(|s| index[s])
+                .collect::<Vec<_>>();
+            s.sort_unstable();
+            succ.push(s);
+            num_prec.push(ts.top[item].prec.len());
+        }
+        let ready = (0..items.len()).filter(|&i| num_prec[i] == 0).collect();
+        AllOrders {
+            empty: ts.is_empty(),
+            items,
+            succ,
+            num_prec,
+            path: Vec::new(),
+            stack: vec![(ready, 0)],
+        }
+    }
+
+    /// Moves to the next complete order, returning whether there was one.
+    fn advance(&mut self) -> bool {
+        if self.empty {
+            self.empty = false;
+            self.stack.clear();
+            return true;
+        }
+        while let Some((ready, tried)) = self.stack.last_mut() {
+            if *tried == ready.len() {
+                let _ = self.stack.pop();
+                if let Some(i) = self.path.pop() {
+                    for &s in &self.succ[i] {
+                        self.num_prec[s] += 1;
+                    }
+                }
+                continue;
+            }
+
+            let i = ready[*tried];
+            *tried += 1;
+            let mut next = ready
+                .iter()
+                .cloned()
+                .filter(|&r| r != i)
+                .collect::<Vec<_>>();
+            for &s in &self.succ[i] {
+                self.num_prec[s] -= 1;
+                if self.num_prec[s] == 0 {
+                    next.push(s);
+                }
+            }
+            next.sort_unstable();
+            self.path.push(i);
+            self.stack.push((next, 0));
+            if self.path.len() == self.items.len() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<'a, T: Hash + Eq + Clone> Iterator for AllOrders<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Vec<&'a T>> {
+        if self.advance() {
+            Some(self.path.iter().map(|&i| self.items[i]).collect())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> fmt::Debug for AllOrders<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AllOrders").finish()
+    }
+}
+
+impl<T: Hash + Eq + Clone> TopologicalSort<T> {
+    /// Returns an iterator over every distinct topological order of the items, without
+    /// removing them.
+    ///
+    /// The number of orders grows exponentially with the number of independent items. Nothing
+    /// is returned if the items cannot be sorted because of cyclic dependencies, and a single
+    /// empty order is returned if there are no items.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("config", "db");
+    /// ts.add_dependency("config", "cache");
+    /// ts.add_dependency("db", "server");
+    /// ts.add_dependency("cache", "server");
+    /// let orders = ts.all_orders().collect::<Vec<_>>();
+    /// assert_eq!(vec![
+    ///     vec![&"config", &"db", &"cache", &"server"],
+    ///     vec![&"config", &"cache", &"db", &"server"],
+    /// ], orders);
+    /// # }
+    /// ```
+    pub fn all_orders(&self) -> AllOrders<'_, T> {
+        AllOrders::new(self)
+    }
+
+    /// Returns the number of distinct topological orders of the items, counting at most up to
+    /// `limit` if one is given.
+    ///
+    /// This enumerates the orders like [`all_orders`](#method.all_orders) does, so a limit
+    /// should be given unless there are few independent items.
+    pub fn count_orders(&self, limit: Option<usize>) -> usize {
+        let mut orders = self.all_orders();
+        let mut count = 0;
+        while limit.map_or(true, |limit| count < limit) && orders.advance() {
+            count += 1;
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TopologicalSort;
+    use quickcheck_macros::quickcheck;
+    use std::collections::HashSet;
+
+    #[test]
+    fn all_orders() {
+        let mut ts = TopologicalSort::<i32>::new();
+        for i in 1..=3 {
+            assert!(ts.insert(i));
+        }
+        assert_eq!(
+            vec![
+                vec![&1, &2, &3],
+                vec![&1, &3, &2],
+                vec![&2, &1, &3],
+                vec![&2, &3, &1],
+                vec![&3, &1, &2],
+                vec![&3, &2, &1],
+            ],
+            ts.all_orders().collect::<Vec<_>>()
+        );
+        assert_eq!(6, ts.count_orders(None));
+        assert_eq!(4, ts.count_orders(Some(4)));
+        assert_eq!(0, ts.count_orders(Some(0)));
+
+        ts.add_dependency(3, 1);
+        assert_eq!(3, ts.count_orders(None));
+        ts.add_dependency(1, 3);
+        assert_eq!(0, ts.all_orders().count());
+
+        let empty = TopologicalSort::<i32>::new();
+        assert_eq!(
+            vec![Vec::<&i32>::new()],
+            empty.all_orders().collect::<Vec<_>>()
+        );
+        assert_eq!(1, empty.count_orders(None));
+    }
+
+    #[quickcheck]
+    fn orders_are_valid_and_distinct(edges: Vec<(u8, u8)>) {
+        let mut ts = TopologicalSort::<u8>::new();
+        for (a, b) in edges {
+            let (a, b) = (a % 7, b % 7);
+            if a != b {
+                ts.add_dependency(a.min(b), a.max(b));
+            }
+        }
+        let mut seen = HashSet::new();
+        for order in ts.all_orders() {
+            assert_eq!(ts.len(), order.len());
+            for (i, prec) in order.iter().enumerate() {
+                for succ in ts.successors(prec).unwrap() {
+                    assert!(!order[..i].contains(&succ));
+                }
+            }
+            assert!(seen.insert(order));
+        }
+        assert_eq!(seen.len(), ts.count_orders(None));
+        assert_eq!(seen.len().min(3), ts.count_orders(Some(3)));
+    }
+}
@@ -0,0 +1,68 @@
+/// A fixed-size set of small integers, stored as one bit per possible member.
+#[derive(Clone)]
+pub(crate) struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    /// Creates an empty set which can hold the integers below `len`.
+    pub(crate) fn new(len: usize) -> BitSet {
+        BitSet {
+            words: vec![0; (len + 63) / 64],
+        }
+    }
+
+    pub(crate) fn contains(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub(crate) fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Adds all members of `other`, which must have been created with the same length.
+    pub(crate) fn union_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= *other;
+        }
+    }
+
+    /// Returns the number of members.
+    pub(crate) fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the members in increasing order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitSet;
+
+    #[test]
+    fn insert_and_union() {
+        let mut a = BitSet::new(130);
+        a.insert(0);
+        a.insert(129);
+        a.insert(129);
+        let mut b = BitSet::new(130);
+        b.insert(64);
+        b.insert(0);
+        a.union_with(&b);
+        assert!(a.contains(64));
+        assert!(!a.contains(63));
+        assert!(a.contains(129));
+        assert_eq!(3, a.count());
+        assert_eq!(vec![0, 64, 129], a.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{CycleError, TopologicalSort};
+
+/// The timing of an item in a [`CriticalPath`](struct.CriticalPath.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timing {
+    /// The duration of the item.
+    pub duration: u64,
+    /// The earliest time the item can start, once all items it depends on are finished.
+    pub earliest_start: u64,
+    /// The latest time the item can start without delaying the end of all items.
+    pub latest_start: u64,
+}
+
+impl Timing {
+    /// Returns the earliest time the item can finish.
+    pub fn earliest_finish(&self) -> u64 {
+        self.earliest_start + self.duration
+    }
+
+    /// Returns the latest time the item can finish without delaying the end of all items.
+    pub fn latest_finish(&self) -> u64 {
+        self.latest_start + self.duration
+    }
+
+    /// Returns how long the item can be delayed without delaying the end of all items.
+    pub fn slack(&self) -> u64 {
+        self.latest_start - self.earliest_start
+    }
+}
+
+/// The result of a critical path analysis, created by [`TopologicalSort::critical_path`].
+///
+/// [`TopologicalSort::critical_path`]: struct.TopologicalSort.html#method.critical_path
+#[derive(Clone, Debug)]
+pub struct CriticalPath<T> {
+    timings: HashMap<T, Timing>,
+    path: Vec<T>,
+    length: u64,
+}
+
+impl<T: Hash + Eq> CriticalPath<T> {
+    /// Returns the time at which all items are finished when every item starts as early as
+    /// possible.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns a longest chain of dependent items, in topological order.
+    ///
+    /// Its durations add up to [`length`](#method.length), and none of its items has any slack.
+    pub fn path(&self) -> &[T] {
+        &self.path
+    }
+
+    /// Returns the timing of an item, or `None` if it was not part of the analysis.
+    pub fn timing(&self, elt: &T) -> Option<&Timing> {
+        self.timings.get(elt)
+    }
+
+    /// Returns the timings of all items.
+    pub fn timings(&self) -> &HashMap<T, Timing> {
+        &self.timings
+    }
+}
+
+impl<T: Hash + Eq + Clone> TopologicalSort<T> {
+    /// Computes the critical path of the items, where `duration` gives how long each item
+    /// takes, assuming any number of items can run at the same time.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("build", "test");
+    /// ts.add_dependency("build", "docs");
+    /// ts.add_dependency("test", "release");
+    /// ts.add_dependency("docs", "release");
+    /// let cp = ts.critical_path(|job| match *job {
+    ///     "build" => 10,
+    ///     "test" => 30,
+    ///     "docs" => 5,
+    ///     _ => 1,
+    /// }).unwrap();
+    /// assert_eq!(41, cp.length());
+    /// assert_eq!(&["build", "test", "release"], cp.path());
+    /// assert_eq!(25, cp.timing(&"docs").unwrap().slack());
+    /// # }
+    /// ```
+    pub fn critical_path<F>(&self, mut duration: F) -> Result<CriticalPath<T>, CycleError<T>>
+    where
+        F: FnMut(&T) -> u64,
+    {
+        let order = self.iter().collect::<Vec<_>>();
+        if order.len() < self.len() {
+            return Err(self.cycle_error(&order));
+        }
+
+        let mut timings = HashMap::<&T, Timing>::with_capacity(order.len());
+        let mut length = 0;
+        for &item in &order {
+            let earliest_start = self.top[item]
+                .prec
+                .iter()
+                .map(|p| timings[p].earliest_finish())
+                .max()
+                .unwrap_or(0);
+            let timing = Timing {
+                duration: duration(item),
+                earliest_start,
+                latest_start: 0,
+            };
+            length = length.max(timing.earliest_finish());
+            let _ = timings.insert(item, timing);
+        }
+
+        for &item in order.iter().rev() {
+            let latest_finish = self.top[item]
+                .succ
+                .iter()
+                .map(|s| timings[s].latest_start)
+                .min()
+                .unwrap_or(length);
+            let timing = timings.get_mut(item).unwrap();
+            timing.latest_start = latest_finish - timing.duration;
+        }
+
+        // Walk back from an item finishing last through items finishing right when the next
+        // one starts.
+        let mut path = Vec::new();
+        let mut next = order
+            .iter()
+            .rev()
+            .find(|&&item| timings[item].earliest_finish() == length)
+            .cloned();
+        while let Some(item) = next {
+            path.push(item.clone());
+            let start = timings[item].earliest_start;
+            next = self.top[item]
+                .prec
+                .iter()
+                .find(|p| timings[p].earliest_finish() == start);
+        }
+        path.reverse();
+
+        Ok(CriticalPath {
+            timings: timings
+                .into_iter()
+                .map(|(item, timing)| (item.clone(), timing))
+                .collect(),
+            path,
+            length,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TopologicalSort;
+
+    #[test]
+    fn critical_path() {
+        let mut ts = TopologicalSort::<char>::new();
+        ts.add_dependency('a', 'b');
+        ts.add_dependency('a', 'c');
+        ts.add_dependency('b', 'd');
+        ts.add_dependency('c', 'd');
+        ts.add_dependency('d', 'e');
+        assert!(ts.insert('f'));
+        let durations = [('a', 3), ('b', 2), ('c', 4), ('d', 1), ('e', 2), ('f', 5)];
+        let cp = ts
+            .critical_path(|n| durations.iter().find(|d| d.0 == *n).unwrap().1)
+            .unwrap();
+
+        assert_eq!(10, cp.length());
+        assert_eq!(&['a', 'c', 'd', 'e'], cp.path());
+        let slack = |n| cp.timing(&n).unwrap().slack();
+        assert_eq!(0, slack('a'));
+        assert_eq!(2, slack('b'));
+        assert_eq!(0, slack('c'));
+        assert_eq!(5, slack('f'));
+        let d = cp.timing(&'d').unwrap();
+        assert_eq!(
+            (7, 7, 8),
+            (d.earliest_start, d.latest_start, d.latest_finish())
+        );
+        assert_eq!(6, cp.timings().len());
+    }
+
+    #[test]
+    fn critical_path_empty_and_cyclic() {
+        let mut ts = TopologicalSort::<char>::new();
+        let cp = ts.critical_path(|_| 1).unwrap();
+        assert_eq!(0, cp.length());
+        assert!(cp.path().is_empty());
+
+        ts.add_dependency('a', 'b');
+        ts.add_dependency('b', 'a');
+        assert!(ts.critical_path(|_| 1).is_err());
+    }
+}
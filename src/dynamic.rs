@@ -0,0 +1,351 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::CycleError;
+
+#[derive(Clone)]
+struct Node<T> {
+    pos: usize,
+    prec: HashSet<T>,
+    succ: HashSet<T>,
+}
+
+/// Maintains a topological order while dependencies are added and removed.
+///
+/// Unlike `TopologicalSort`, items are never removed by sorting: the current order can be read
+/// at any time with [`iter`](#method.iter). Adding a dependency only reorders the items between
+/// its two ends which are affected by it, using the algorithm of Pearce and Kelly, and
+/// dependencies which would create a cycle are rejected.
+///
+/// ```rust
+/// # extern crate topological_sort;
+/// # fn main() {
+/// use topological_sort::DynamicTopologicalSort;
+/// let mut ts = DynamicTopologicalSort::<&str>::new();
+/// ts.add_dependency("hello_world.o", "hello_world").unwrap();
+/// ts.add_dependency("hello_world.c", "hello_world.o").unwrap();
+/// assert_eq!(vec![&"hello_world.c", &"hello_world.o", &"hello_world"],
+///            ts.iter().collect::<Vec<_>>());
+/// assert!(ts.add_dependency("hello_world", "hello_world.c").is_err());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DynamicTopologicalSort<T> {
+    nodes: HashMap<T, Node<T>>,
+    // Items by position in the order. Removed items leave a hole until the next compaction.
+    slots: Vec<Option<T>>,
+}
+
+impl<T> Default for DynamicTopologicalSort<T> {
+    fn default() -> DynamicTopologicalSort<T> {
+        DynamicTopologicalSort {
+            nodes: HashMap::new(),
+            slots: Vec::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> DynamicTopologicalSort<T> {
+    /// Creates new empty `DynamicTopologicalSort`.
+    #[inline]
+    pub fn new() -> DynamicTopologicalSort<T> {
+        Default::default()
+    }
+
+    /// Returns the number of elements in the `DynamicTopologicalSort`.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if the `DynamicTopologicalSort` contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns true if the `DynamicTopologicalSort` contains the element.
+    pub fn contains(&self, elt: &T) -> bool {
+        self.nodes.contains_key(elt)
+    }
+
+    /// Returns true if `succ` directly depends on `prec`.
+    pub fn contains_dependency(&self, prec: &T, succ: &T) -> bool {
+        self.nodes
+            .get(prec)
+            .map_or(false, |node| node.succ.contains(succ))
+    }
+
+    /// Inserts an element at the end of the order, without adding any dependencies from or to
+    /// it.
+    ///
+    /// If the `DynamicTopologicalSort` did not have this element present, `true` is returned.
+    ///
+    /// If the `DynamicTopologicalSort` already had this element present, `false` is returned.
+    pub fn insert<U>(&mut self, elt: U) -> bool
+    where
+        U: Into<T>,
+    {
+        match self.nodes.entry(elt.into()) {
+            Entry::Vacant(e) => {
+                self.slots.push(Some(e.key().clone()));
+                let _ = e.insert(Node {
+                    pos: self.slots.len() - 1,
+                    prec: HashSet::new(),
+                    succ: HashSet::new(),
+                });
+                true
+            }
+            Entry::Occupied(_) => false,
+        }
+    }
+
+    /// Registers the two elements' dependency, moving `succ` after `prec` if needed.
+    ///
+    /// If `prec` already depends on `succ`, directly or indirectly, the dependency is not
+    /// registered and an error is returned, as with `TopologicalSort::try_add_dependency`. Both
+    /// elements are inserted in any case.
+    ///
+    /// # Arguments
+    ///
+    /// * `prec` - The element appears before `succ`. `prec` is depended on by `succ`.
+    /// * `succ` - The element appears after `prec`. `succ` depends on `prec`.
+    pub fn add_dependency<P, S>(&mut self, prec: P, succ: S) -> Result<(), CycleError<T>>
+    where
+        P: Into<T>,
+        S: Into<T>,
+    {
+        let (prec, succ) = (prec.into(), succ.into());
+        let _ = self.insert(prec.clone());
+        let _ = self.insert(succ.clone());
+        if prec == succ {
+            return Err(self.cycle_error(&prec, &succ));
+        }
+        if self.contains_dependency(&prec, &succ) {
+            return Ok(());
+        }
+
+        let lower = self.nodes[&succ].pos;
+        let upper = self.nodes[&prec].pos;
+        if lower < upper {
+            // Only the items between `succ` and `prec` may need to move: the ones reachable
+            // from `succ` must end up after the ones `prec` depends on.
+            let forward = match self.search(&succ, upper, true) {
+                Some(forward) => forward,
+                None => return Err(self.cycle_error(&prec, &succ)),
+            };
+            let backward = self.search(&prec, lower, false).unwrap();
+            self.reorder(backward, forward);
+        }
+
+        let _ = self.nodes.get_mut(&prec).unwrap().succ.insert(succ.clone());
+        let _ = self.nodes.get_mut(&succ).unwrap().prec.insert(prec);
+        Ok(())
+    }
+
+    /// Removes the two elements' dependency, keeping both elements.
+    ///
+    /// The order is left unchanged. If the dependency was registered, `true` is returned.
+    /// Otherwise `false` is returned.
+    pub fn remove_dependency(&mut self, prec: &T, succ: &T) -> bool {
+        if !self
+            .nodes
+            .get_mut(prec)
+            .map_or(false, |node| node.succ.remove(succ))
+        {
+            return false;
+        }
+        let _ = self.nodes.get_mut(succ).unwrap().prec.remove(prec);
+        true
+    }
+
+    /// Removes an element together with all dependencies from or to it.
+    ///
+    /// If the `DynamicTopologicalSort` had this element present, `true` is returned. Otherwise
+    /// `false` is returned.
+    pub fn remove_node(&mut self, elt: &T) -> bool {
+        let node = match self.nodes.remove(elt) {
+            Some(node) => node,
+            None => return false,
+        };
+        for p in &node.prec {
+            let _ = self.nodes.get_mut(p).unwrap().succ.remove(elt);
+        }
+        for s in &node.succ {
+            let _ = self.nodes.get_mut(s).unwrap().prec.remove(elt);
+        }
+        self.slots[node.pos] = None;
+        if self.slots.len() > 2 * self.nodes.len() {
+            self.compact();
+        }
+        true
+    }
+
+    /// Returns an iterator over the elements in the current topological order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.slots.iter().flatten()
+    }
+
+    /// Returns the error caused by a dependency from `prec` to `succ`, which must close a cycle.
+    fn cycle_error(&self, prec: &T, succ: &T) -> CycleError<T> {
+        CycleError::for_dependency(prec, succ, |item| {
+            self.nodes.get(item).map(|node| &node.succ)
+        })
+        .unwrap()
+    }
+
+    /// Collects the items reachable from `start` whose positions are strictly between `start`
+    /// and `bound`, following successors if `forward` is set and predecessors otherwise.
+    ///
+    /// Returns `None` if the item at `bound` is reachable.
+    fn search(&self, start: &T, bound: usize, forward: bool) -> Option<Vec<T>> {
+        let in_range = |pos: usize| if forward { pos < bound } else { pos > bound };
+        let mut visited = HashSet::new();
+        let mut stack = vec![start];
+        let _ = visited.insert(start);
+        while let Some(item) = stack.pop() {
+            let node = &self.nodes[item];
+            let next = if forward { &node.succ } else { &node.prec };
+            for n in next {
+                let pos = self.nodes[n].pos;
+                if pos == bound {
+                    return None;
+                }
+                if in_range(pos) && visited.insert(n) {
+                    stack.push(n);
+                }
+            }
+        }
+        Some(visited.into_iter().cloned().collect())
+    }
+
+    /// Moves the items of `backward` before the items of `forward`, reusing their positions.
+    fn reorder(&mut self, mut backward: Vec<T>, mut forward: Vec<T>) {
+        backward.sort_by_key(|item| self.nodes[item].pos);
+        forward.sort_by_key(|item| self.nodes[item].pos);
+        let mut positions = backward
+            .iter()
+            .chain(&forward)
+            .map(|item| self.nodes[item].pos)
+            .collect::<Vec<_>>();
+        positions.sort_unstable();
+
+        for (item, pos) in backward.into_iter().chain(forward).zip(positions) {
+            self.nodes.get_mut(&item).unwrap().pos = pos;
+            self.slots[pos] = Some(item);
+        }
+    }
+
+    /// Removes the holes left by removed items.
+    fn compact(&mut self) {
+        self.slots.retain(Option::is_some);
+        for (pos, item) in self.slots.iter().enumerate() {
+            self.nodes.get_mut(item.as_ref().unwrap()).unwrap().pos = pos;
+        }
+    }
+}
+
+impl<T: fmt::Debug + Hash + Eq + Clone> fmt::Debug for DynamicTopologicalSort<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DynamicTopologicalSort;
+    use crate::TopologicalSort;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn reorder() {
+        let mut ts = DynamicTopologicalSort::<i32>::new();
+        for i in 1..=6 {
+            assert!(ts.insert(i));
+        }
+        ts.add_dependency(5, 2).unwrap();
+        ts.add_dependency(2, 1).unwrap();
+        ts.add_dependency(6, 4).unwrap();
+        assert_eq!(vec![&5, &2, &3, &6, &1, &4], ts.iter().collect::<Vec<_>>());
+
+        let err = ts.add_dependency(1, 5).unwrap_err();
+        assert_eq!(&[1, 5, 2], err.cycle());
+        assert!(!ts.contains_dependency(&1, &5));
+
+        assert!(ts.remove_dependency(&2, &1));
+        ts.add_dependency(1, 5).unwrap();
+        assert_eq!(vec![&1, &5, &3, &6, &2, &4], ts.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn self_dependency() {
+        let mut ts = DynamicTopologicalSort::<&str>::new();
+        ts.add_dependency("axe", "sharp").unwrap();
+        ts.add_dependency("sharp", "water").unwrap();
+        let err = ts.add_dependency("axe", "axe").unwrap_err();
+        assert_eq!(&["axe"], err.cycle());
+        let mut unsorted = err.into_unsorted();
+        unsorted.sort_unstable();
+        assert_eq!(vec!["axe", "sharp", "water"], unsorted);
+
+        assert!(ts.add_dependency("stone", "stone").is_err());
+        assert!(ts.contains(&"stone"));
+        assert!(!ts.contains_dependency(&"stone", &"stone"));
+    }
+
+    #[test]
+    fn remove_node() {
+        let mut ts = DynamicTopologicalSort::<i32>::new();
+        for i in 0..10 {
+            ts.add_dependency(i, i + 1).unwrap();
+        }
+        for i in 0..8 {
+            assert!(ts.remove_node(&i));
+        }
+        assert!(!ts.remove_node(&0));
+        assert_eq!(3, ts.len());
+        assert!(ts.add_dependency(10, 8).is_err());
+        ts.add_dependency(20, 8).unwrap();
+        assert_eq!(vec![&20, &8, &9, &10], ts.iter().collect::<Vec<_>>());
+    }
+
+    #[quickcheck]
+    fn matches_topological_sort(edges: Vec<(u8, u8)>, removed: Vec<(u8, u8)>) {
+        let mut dynamic = DynamicTopologicalSort::<u8>::new();
+        let mut ts = TopologicalSort::<u8>::new();
+        for (prec, succ) in edges {
+            let _ = ts.insert(prec);
+            let _ = ts.insert(succ);
+            match (
+                ts.try_add_dependency(prec, succ),
+                dynamic.add_dependency(prec, succ),
+            ) {
+                (Ok(()), Ok(())) => {}
+                (Err(expected), Err(err)) => {
+                    let mut expected = expected.into_unsorted();
+                    let mut unsorted = err.into_unsorted();
+                    expected.sort_unstable();
+                    unsorted.sort_unstable();
+                    assert_eq!(expected, unsorted);
+                }
+                (expected, result) => panic!("expected {:?}, got {:?}", expected, result),
+            }
+        }
+        for (prec, succ) in removed {
+            assert_eq!(
+                ts.remove_dependency(&prec, &succ),
+                dynamic.remove_dependency(&prec, &succ)
+            );
+        }
+
+        let order = dynamic.iter().collect::<Vec<_>>();
+        assert_eq!(ts.len(), order.len());
+        for (i, prec) in order.iter().enumerate() {
+            for succ in ts.successors(prec).unwrap() {
+                assert!(order[..i].iter().all(|x| x != &succ));
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+/// An error returned when items cannot be sorted because of cyclic dependencies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError<T> {
+    unsorted: Vec<T>,
+    cycle: Vec<T>,
+}
+
+impl<T> CycleError<T> {
+    pub(crate) fn new(unsorted: Vec<T>, cycle: Vec<T>) -> CycleError<T> {
+        CycleError { unsorted, cycle }
+    }
+
+    /// Returns the error which adding a dependency from `prec` to `succ` would cause, if any.
+    ///
+    /// `succs` returns the items directly depending on an item. The cycle of the error starts
+    /// with `prec` and `succ`, or is only `prec` if both are the same item, and its unsorted
+    /// items are all items reachable from `succ`.
+    pub(crate) fn for_dependency<'a, F>(prec: &'a T, succ: &'a T, succs: F) -> Option<CycleError<T>>
+    where
+        T: Hash + Eq + Clone,
+        F: Fn(&'a T) -> Option<&'a HashSet<T>>,
+    {
+        // Maps every item reachable from `succ` to the item it was reached from.
+        let mut parent = HashMap::<&T, &T>::new();
+        let mut stack = vec![succ];
+        let _ = parent.insert(succ, succ);
+        while let Some(item) = stack.pop() {
+            for next in succs(item).into_iter().flatten() {
+                if let Entry::Vacant(e) = parent.entry(next) {
+                    let _ = e.insert(item);
+                    stack.push(next);
+                }
+            }
+        }
+        if !parent.contains_key(prec) {
+            return None;
+        }
+
+        let mut cycle = vec![prec.clone()];
+        if prec != succ {
+            let mut item = parent[prec];
+            while item != succ {
+                cycle.push(item.clone());
+                item = parent[item];
+            }
+            cycle.push(succ.clone());
+            cycle[1..].reverse();
+        }
+        let unsorted = parent.keys().map(|&k| k.clone()).collect();
+        Some(CycleError::new(unsorted, cycle))
+    }
+
+    /// Returns the items which could not be sorted, in an unspecified order.
+    ///
+    /// These are the items which are part of a cycle or which depend on an item which is.
+    pub fn unsorted(&self) -> &[T] {
+        &self.unsorted
+    }
+
+    /// Returns one of the cycles which prevented sorting.
+    ///
+    /// The items are returned in dependency order as in `TopologicalSort::find_cycle`.
+    pub fn cycle(&self) -> &[T] {
+        &self.cycle
+    }
+
+    /// Converts the error into the items which could not be sorted.
+    pub fn into_unsorted(self) -> Vec<T> {
+        self.unsorted
+    }
+}
+
+impl<T: fmt::Debug> fmt::Display for CycleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cyclic dependency")?;
+        if let Some(first) = self.cycle.first() {
+            write!(f, ": ")?;
+            for item in &self.cycle {
+                write!(f, "{:?} -> ", item)?;
+            }
+            write!(f, "{:?}", first)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug> Error for CycleError<T> {}
+
+/// The reason why a sequence is not a valid topological order, returned by
+/// [`TopologicalSort::is_valid_order`].
+///
+/// [`TopologicalSort::is_valid_order`]: struct.TopologicalSort.html#method.is_valid_order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderViolation<T> {
+    /// The item is not in the `TopologicalSort`.
+    Unknown(T),
+    /// The item appears more than once.
+    Duplicate(T),
+    /// The item is in the `TopologicalSort` but does not appear.
+    Missing(T),
+    /// `succ` depends on `prec` but does not appear after it, either because it appears before
+    /// it or because both are the same item.
+    Dependency {
+        /// The element which is depended upon by `succ`.
+        prec: T,
+        /// The element which depends on `prec`.
+        succ: T,
+    },
+}
+
+impl<T: fmt::Debug> fmt::Display for OrderViolation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderViolation::Unknown(item) => write!(f, "unknown item {:?}", item),
+            OrderViolation::Duplicate(item) => write!(f, "duplicate item {:?}", item),
+            OrderViolation::Missing(item) => write!(f, "missing item {:?}", item),
+            OrderViolation::Dependency { prec, succ } => {
+                write!(
+                    f,
+                    "{:?} depends on {:?} but does not appear after it",
+                    succ, prec
+                )
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for OrderViolation<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::{CycleError, OrderViolation};
+
+    #[test]
+    fn display() {
+        let err = CycleError::new(vec!["a", "b", "c"], vec!["a", "b"]);
+        assert_eq!(r#"cyclic dependency: "a" -> "b" -> "a""#, err.to_string());
+        let err = CycleError::<i32>::new(vec![], vec![]);
+        assert_eq!("cyclic dependency", err.to_string());
+
+        let err = OrderViolation::Dependency { prec: 1, succ: 2 };
+        assert_eq!(
+            "2 depends on 1 but does not appear after it",
+            err.to_string()
+        );
+        assert_eq!("missing item 3", OrderViolation::Missing(3).to_string());
+    }
+}
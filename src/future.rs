@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+
+use crate::{CycleError, OnError, Outcome, Scheduler, TopologicalSort};
+
+/// Runs the task of every item concurrently, starting each one as soon as the tasks of the items
+/// it depends on have succeeded and fewer than `limit` tasks are running.
+///
+/// The returned future does not depend on any particular executor. Returns the outcome of every
+/// item. The items depending on an item whose task failed are skipped; `on_error` decides whether
+/// the other items keep running. If some items can not be sorted because of cyclic dependencies,
+/// no task is run and an error is returned. A `limit` of 0 is treated as 1.
+///
+/// This function is only available with the `async` feature.
+///
+/// ```rust
+/// # extern crate topological_sort;
+/// # use std::future::Future;
+/// # use std::sync::Arc;
+/// # use std::task::{Context, Poll, Wake};
+/// # use std::thread::{self, Thread};
+/// # struct ThreadWaker(Thread);
+/// # impl Wake for ThreadWaker {
+/// #     fn wake(self: Arc<Self>) {
+/// #         self.0.unpark();
+/// #     }
+/// # }
+/// # fn block_on<F: Future>(future: F) -> F::Output {
+/// #     let mut future = Box::pin(future);
+/// #     let waker = Arc::new(ThreadWaker(thread::current())).into();
+/// #     let mut cx = Context::from_waker(&waker);
+/// #     loop {
+/// #         if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+/// #             return output;
+/// #         }
+/// #         thread::park();
+/// #     }
+/// # }
+/// # fn main() {
+/// use topological_sort::{async_execute, OnError, Outcome, TopologicalSort};
+/// let mut ts = TopologicalSort::<&str>::new();
+/// ts.add_dependency("provision", "deploy");
+/// ts.add_dependency("build", "deploy");
+/// // `block_on` can be the one of any executor, such as `futures::executor::block_on`.
+/// let outcomes = block_on(async_execute(ts, 2, OnError::Stop, |step| async move {
+///     Ok::<_, ()>(step.len())
+/// })).unwrap();
+/// assert_eq!(Outcome::Completed(6), outcomes[&"deploy"]);
+/// # }
+/// ```
+pub async fn async_execute<T, O, E, F, Fut>(
+    ts: TopologicalSort<T>,
+    limit: usize,
+    on_error: OnError,
+    mut task: F,
+) -> Result<HashMap<T, Outcome<O, E>>, CycleError<T>>
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<O, E>>,
+{
+    let items = ts.iter().cloned().collect::<Vec<_>>();
+    if items.len() < ts.len() {
+        return Err(ts.into_cycle_error());
+    }
+
+    let limit = limit.max(1);
+    let mut scheduler = Scheduler::new(ts);
+    let mut outcomes = HashMap::with_capacity(items.len());
+    let mut running = FuturesUnordered::new();
+    let mut stopped = false;
+    loop {
+        while !stopped && running.len() < limit {
+            let item = match scheduler.take() {
+                Some(item) => item,
+                None => break,
+            };
+            let future = task(item.clone());
+            running.push(async move { (item, future.await) });
+        }
+
+        let (item, result) = match running.next().await {
+            Some(finished) => finished,
+            None => break,
+        };
+        let outcome = match result {
+            Ok(output) => {
+                let _ = scheduler.complete(&item);
+                Outcome::Completed(output)
+            }
+            Err(err) => {
+                stopped |= on_error == OnError::Stop;
+                for skipped in scheduler.fail(&item).unwrap_or_default() {
+                    let _ = outcomes.insert(skipped, Outcome::Skipped);
+                }
+                Outcome::Failed(err)
+            }
+        };
+        let _ = outcomes.insert(item, outcome);
+    }
+
+    for item in items {
+        let _ = outcomes.entry(item).or_insert(Outcome::Skipped);
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::async_execute;
+    use crate::{OnError, Outcome, TopologicalSort};
+    use std::cell::Cell;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    /// Wakes a thread parked by `block_on`.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Runs a future to completion on the current thread.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Arc::new(ThreadWaker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// A future which is pending for the given number of polls.
+    struct Yield(usize);
+
+    impl Future for Yield {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn bounded_concurrency() {
+        let mut ts = TopologicalSort::<i32>::new();
+        for i in 1..20 {
+            ts.add_dependency(0, i);
+            ts.add_dependency(i, 20);
+        }
+        let running = Cell::new(0);
+        let max_running = Cell::new(0);
+        let outcomes = block_on(async_execute(ts, 3, OnError::Stop, |i| {
+            let (running, max_running) = (&running, &max_running);
+            async move {
+                running.set(running.get() + 1);
+                max_running.set(max_running.get().max(running.get()));
+                Yield(i as usize % 4).await;
+                running.set(running.get() - 1);
+                Ok::<_, ()>(i)
+            }
+        }))
+        .unwrap();
+        assert_eq!(21, outcomes.len());
+        assert!(outcomes.iter().all(|(i, o)| *o == Outcome::Completed(*i)));
+        assert_eq!(3, max_running.get());
+    }
+
+    #[test]
+    fn continue_on_error() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(4, 5);
+        let outcomes = block_on(async_execute(ts, 1, OnError::Continue, |i| async move {
+            if i == 2 {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        }))
+        .unwrap();
+        assert_eq!(Outcome::Completed(()), outcomes[&1]);
+        assert_eq!(Outcome::Failed("failed"), outcomes[&2]);
+        assert_eq!(Outcome::Skipped, outcomes[&3]);
+        assert_eq!(Outcome::Completed(()), outcomes[&4]);
+        assert_eq!(Outcome::Completed(()), outcomes[&5]);
+    }
+}
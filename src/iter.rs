@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::ready::ReadySet;
+use crate::{CycleError, TopologicalSort};
+
+/// An iterator over the items of a `TopologicalSort` in topological order, which does not
+/// modify it.
+///
+/// Ready items are returned in the `SortOrder` of the `TopologicalSort`. Items which are part
+/// of a cycle, or which depend on such items, are never returned.
+///
+/// This `struct` is created by [`TopologicalSort::iter`].
+///
+/// [`TopologicalSort::iter`]: struct.TopologicalSort.html#method.iter
+pub struct Iter<'a, T> {
+    ts: &'a TopologicalSort<T>,
+    num_prec: HashMap<&'a T, usize>,
+    ready: ReadySet<&'a T>,
+}
+
+impl<'a, T: Hash + Eq + Clone> Iter<'a, T> {
+    pub(crate) fn new(ts: &'a TopologicalSort<T>) -> Iter<'a, T> {
+        let mut iter = Iter {
+            ts,
+            num_prec: HashMap::new(),
+            ready: ReadySet::default(),
+        };
+        for item in ts.ready.iter() {
+            iter.push_ready(item);
+        }
+        iter
+    }
+
+    /// Returns all items which are ready, and releases the items depending on them.
+    pub(crate) fn next_layer(&mut self) -> Vec<&'a T> {
+        let mut layer = self.ready.take_all();
+        layer.sort_by(|a, b| self.ts.order.compare(&self.ts.top, a, b));
+        for item in &layer {
+            self.release(item);
+        }
+        layer
+    }
+
+    fn push_ready(&mut self, item: &'a T) {
+        let (order, top) = (&self.ts.order, &self.ts.top);
+        self.ready.push(item, |a, b| order.compare(top, a, b));
+    }
+
+    fn release(&mut self, prec: &'a T) {
+        let top = &self.ts.top;
+        for succ in &top[prec].succ {
+            let num_prec = self
+                .num_prec
+                .entry(succ)
+                .or_insert_with(|| top[succ].prec.len());
+            *num_prec -= 1;
+            if *num_prec == 0 {
+                self.push_ready(succ);
+            }
+        }
+    }
+}
+
+impl<'a, T: Hash + Eq + Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let (order, top) = (&self.ts.order, &self.ts.top);
+        let item = self.ready.pop(|a, b| order.compare(top, a, b))?;
+        self.release(item);
+        Some(item)
+    }
+}
+
+impl<'a, T> fmt::Debug for Iter<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Iter").finish()
+    }
+}
+
+/// An iterator which removes the items of a `TopologicalSort` in topological order, and reports
+/// an error if some items are left because of cyclic dependencies.
+///
+/// This `struct` is created by [`TopologicalSort::into_try_iter`].
+///
+/// [`TopologicalSort::into_try_iter`]: struct.TopologicalSort.html#method.into_try_iter
+pub struct TryIter<T> {
+    ts: Option<TopologicalSort<T>>,
+}
+
+impl<T> TryIter<T> {
+    pub(crate) fn new(ts: TopologicalSort<T>) -> TryIter<T> {
+        TryIter { ts: Some(ts) }
+    }
+}
+
+impl<T: Hash + Eq + Clone> Iterator for TryIter<T> {
+    type Item = Result<T, CycleError<T>>;
+
+    fn next(&mut self) -> Option<Result<T, CycleError<T>>> {
+        if let Some(item) = self.ts.as_mut()?.pop() {
+            return Some(Ok(item));
+        }
+        let ts = self.ts.take().unwrap();
+        if ts.is_empty() {
+            None
+        } else {
+            Some(Err(ts.into_cycle_error()))
+        }
+    }
+}
+
+impl<T: fmt::Debug + Hash + Eq + Clone> fmt::Debug for TryIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TryIter").field("ts", &self.ts).finish()
+    }
+}
@@ -33,17 +33,54 @@ use std::fmt;
 use std::hash::Hash;
 use std::iter::FromIterator;
 
+pub use all_orders::AllOrders;
+pub use critical_path::{CriticalPath, Timing};
+pub use dynamic::DynamicTopologicalSort;
+pub use error::{CycleError, OrderViolation};
+#[cfg(feature = "async")]
+pub use future::async_execute;
+pub use iter::{Iter, TryIter};
+pub use order::SortOrder;
+#[cfg(feature = "rayon")]
+pub use par::par_execute;
+pub use scheduler::{OnError, Outcome, Scheduler};
+pub use simulate::{Assignment, Heuristic, Schedule};
+pub use transitive::TransitiveClosure;
+
+use ready::ReadySet;
+
+mod all_orders;
+mod bitset;
+mod critical_path;
+mod dynamic;
+mod error;
+#[cfg(feature = "async")]
+mod future;
+mod iter;
+mod order;
+#[cfg(feature = "rayon")]
+mod par;
+mod random;
+mod ready;
+mod scheduler;
+mod simulate;
+mod transitive;
+
 #[derive(Clone)]
 struct Dependency<T> {
-    num_prec: usize,
+    prec: HashSet<T>,
     succ: HashSet<T>,
+    seq: u64,
+    priority: i64,
 }
 
 impl<T: Hash + Eq> Dependency<T> {
-    fn new() -> Dependency<T> {
+    fn new(seq: u64) -> Dependency<T> {
         Dependency {
-            num_prec: 0,
+            prec: HashSet::new(),
             succ: HashSet::new(),
+            seq,
+            priority: 0,
         }
     }
 }
@@ -52,12 +89,18 @@ impl<T: Hash + Eq> Dependency<T> {
 #[derive(Clone)]
 pub struct TopologicalSort<T> {
     top: HashMap<T, Dependency<T>>,
+    ready: ReadySet<T>,
+    order: SortOrder<T>,
+    next_seq: u64,
 }
 
 impl<T> Default for TopologicalSort<T> {
     fn default() -> TopologicalSort<T> {
         TopologicalSort {
             top: HashMap::new(),
+            ready: ReadySet::default(),
+            order: SortOrder::default(),
+            next_seq: 0,
         }
     }
 }
@@ -88,6 +131,40 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
         Default::default()
     }
 
+    /// Creates new empty `TopologicalSort` which returns ready items in the given order.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::{SortOrder, TopologicalSort};
+    /// let mut ts = TopologicalSort::<&str>::with_order(SortOrder::ord());
+    /// ts.add_dependency("stdio.h", "hello_world.o");
+    /// ts.add_dependency("hello_world.c", "hello_world.o");
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// ts.add_dependency("glibc.so", "hello_world");
+    /// assert_eq!(vec!["glibc.so", "hello_world.c", "stdio.h"], ts.pop_all());
+    /// assert_eq!(Some("hello_world.o"), ts.pop());
+    /// # }
+    /// ```
+    pub fn with_order(order: SortOrder<T>) -> TopologicalSort<T> {
+        TopologicalSort {
+            order,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the order in which ready items are returned.
+    pub fn order(&self) -> &SortOrder<T> {
+        &self.order
+    }
+
+    /// Changes the order in which ready items are returned.
+    pub fn set_order(&mut self, order: SortOrder<T>) {
+        self.order = order;
+        let (order, top) = (&self.order, &self.top);
+        self.ready.rebuild(|a, b| order.compare(top, a, b));
+    }
+
     /// Returns the number of elements in the `TopologicalSort`.
     #[inline]
     pub fn len(&self) -> usize {
@@ -100,6 +177,174 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
         self.top.is_empty()
     }
 
+    /// Returns true if the `TopologicalSort` contains the element.
+    pub fn contains(&self, elt: &T) -> bool {
+        self.top.contains_key(elt)
+    }
+
+    /// Returns true if `succ` directly depends on `prec`.
+    pub fn contains_dependency(&self, prec: &T, succ: &T) -> bool {
+        self.top
+            .get(prec)
+            .map_or(false, |dep| dep.succ.contains(succ))
+    }
+
+    /// Returns the elements which the element directly depends on, or `None` if the
+    /// `TopologicalSort` does not contain it.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("hello_world.c", "hello_world.o");
+    /// ts.add_dependency("stdio.h", "hello_world.o");
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// let mut precs = ts.predecessors(&"hello_world.o").unwrap().iter().collect::<Vec<_>>();
+    /// precs.sort();
+    /// assert_eq!(vec![&"hello_world.c", &"stdio.h"], precs);
+    /// assert_eq!(Some(0), ts.in_degree(&"stdio.h"));
+    /// assert_eq!(None, ts.predecessors(&"glibc.so"));
+    /// # }
+    /// ```
+    pub fn predecessors(&self, elt: &T) -> Option<&HashSet<T>> {
+        self.top.get(elt).map(|dep| &dep.prec)
+    }
+
+    /// Returns the elements which directly depend on the element, or `None` if the
+    /// `TopologicalSort` does not contain it.
+    pub fn successors(&self, elt: &T) -> Option<&HashSet<T>> {
+        self.top.get(elt).map(|dep| &dep.succ)
+    }
+
+    /// Returns the number of elements which the element directly depends on, or `None` if the
+    /// `TopologicalSort` does not contain it.
+    pub fn in_degree(&self, elt: &T) -> Option<usize> {
+        self.top.get(elt).map(|dep| dep.prec.len())
+    }
+
+    /// Returns the number of elements which directly depend on the element, or `None` if the
+    /// `TopologicalSort` does not contain it.
+    pub fn out_degree(&self, elt: &T) -> Option<usize> {
+        self.top.get(elt).map(|dep| dep.succ.len())
+    }
+
+    /// Returns all elements which the element depends on, directly or indirectly.
+    ///
+    /// These are the elements which must come before it. The element itself is only included if
+    /// it is part of a cycle.
+    pub fn ancestors(&self, elt: &T) -> HashSet<&T> {
+        self.reachable(elt, |dep| &dep.prec)
+    }
+
+    /// Returns all elements which depend on the element, directly or indirectly.
+    ///
+    /// These are the elements which must come after it. The element itself is only included if
+    /// it is part of a cycle.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("config.h", "main.o");
+    /// ts.add_dependency("config.h", "util.o");
+    /// ts.add_dependency("main.o", "app");
+    /// ts.add_dependency("util.o", "app");
+    /// ts.add_dependency("readme.md", "docs");
+    /// let mut rebuilt = ts.descendants(&"config.h").into_iter().collect::<Vec<_>>();
+    /// rebuilt.sort();
+    /// assert_eq!(vec![&"app", &"main.o", &"util.o"], rebuilt);
+    /// assert!(ts.is_reachable(&"config.h", &"app"));
+    /// assert!(!ts.is_reachable(&"app", &"config.h"));
+    /// # }
+    /// ```
+    pub fn descendants(&self, elt: &T) -> HashSet<&T> {
+        self.reachable(elt, |dep| &dep.succ)
+    }
+
+    /// Returns true if `to` depends on `from`, directly or indirectly.
+    ///
+    /// An element only depends on itself if it is part of a cycle.
+    pub fn is_reachable(&self, from: &T, to: &T) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(item) = stack.pop() {
+            for next in self.top.get(item).into_iter().flat_map(|dep| &dep.succ) {
+                if next == to {
+                    return true;
+                }
+                if visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns a new `TopologicalSort` with only the targets and the elements they depend on,
+    /// directly or indirectly, with the dependencies among them.
+    ///
+    /// Targets which the `TopologicalSort` does not contain are ignored. The sort order and the
+    /// priorities of the elements are kept.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("libc", "libfoo");
+    /// ts.add_dependency("libfoo", "foo-cli");
+    /// ts.add_dependency("libbar", "bar-cli");
+    /// ts.add_dependency("libc", "bar-cli");
+    /// let mut needed = ts.subgraph_for(&["foo-cli"]);
+    /// assert_eq!(vec!["libc", "libfoo", "foo-cli"], needed.collect::<Vec<_>>());
+    /// # }
+    /// ```
+    pub fn subgraph_for<'a, I>(&self, targets: I) -> TopologicalSort<T>
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        let mut items = HashSet::new();
+        for target in targets {
+            if let Some((target, _)) = self.top.get_key_value(target) {
+                let _ = items.insert(target);
+                items.extend(self.ancestors(target));
+            }
+        }
+
+        let mut items = items.into_iter().collect::<Vec<_>>();
+        items.sort_by_key(|&item| self.top[item].seq);
+        let mut subgraph = TopologicalSort::with_order(self.order.clone());
+        for &item in &items {
+            let _ = subgraph.insert_with_priority(item.clone(), self.top[item].priority);
+        }
+        for &item in &items {
+            for prec in &self.top[item].prec {
+                subgraph.add_dependency_impl(prec.clone(), item.clone());
+            }
+        }
+        subgraph
+    }
+
+    /// Returns the elements reachable from `elt` by following `next` at least once.
+    fn reachable<F>(&self, elt: &T, next: F) -> HashSet<&T>
+    where
+        F: Fn(&Dependency<T>) -> &HashSet<T>,
+    {
+        let mut visited = HashSet::new();
+        let mut stack = vec![elt];
+        while let Some(item) = stack.pop() {
+            for n in self.top.get(item).into_iter().flat_map(&next) {
+                if visited.insert(n) {
+                    stack.push(n);
+                }
+            }
+        }
+        visited
+    }
+
     /// Registers the two elements' dependency.
     ///
     /// # Arguments
@@ -115,11 +360,14 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
     }
 
     fn add_dependency_impl(&mut self, prec: T, succ: T) {
-        match self.top.entry(prec) {
+        match self.top.entry(prec.clone()) {
             Entry::Vacant(e) => {
-                let mut dep = Dependency::new();
+                let mut dep = Dependency::new(self.next_seq);
+                self.next_seq += 1;
                 let _ = dep.succ.insert(succ.clone());
+                let prec = e.key().clone();
                 let _ = e.insert(dep);
+                self.push_ready(prec);
             }
             Entry::Occupied(e) => {
                 if !e.into_mut().succ.insert(succ.clone()) {
@@ -131,16 +379,138 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
 
         match self.top.entry(succ) {
             Entry::Vacant(e) => {
-                let mut dep = Dependency::new();
-                dep.num_prec += 1;
+                let mut dep = Dependency::new(self.next_seq);
+                self.next_seq += 1;
+                let _ = dep.prec.insert(prec);
                 let _ = e.insert(dep);
             }
             Entry::Occupied(e) => {
-                e.into_mut().num_prec += 1;
+                let was_ready = e.get().prec.is_empty();
+                let succ = e.key().clone();
+                let _ = e.into_mut().prec.insert(prec);
+                if was_ready {
+                    self.remove_ready(&succ);
+                }
             }
         }
     }
 
+    /// Registers the two elements' dependency, unless it would create cyclic dependencies.
+    ///
+    /// If `prec` already depends on `succ`, directly or indirectly, nothing is registered and an
+    /// error is returned. Its cycle starts with `prec` and `succ`, and its unsorted items are
+    /// the items which would have been part of the cycle or would have depended on it.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// assert!(ts.try_add_dependency("a", "b").is_ok());
+    /// assert!(ts.try_add_dependency("b", "c").is_ok());
+    /// assert_eq!(&["c", "a", "b"], ts.try_add_dependency("c", "a").unwrap_err().cycle());
+    /// assert!(!ts.contains_dependency(&"c", &"a"));
+    /// # }
+    /// ```
+    pub fn try_add_dependency<P, S>(&mut self, prec: P, succ: S) -> Result<(), CycleError<T>>
+    where
+        P: Into<T>,
+        S: Into<T>,
+    {
+        let (prec, succ) = (prec.into(), succ.into());
+        if let Some(err) = self.check_dependency(&prec, &succ) {
+            return Err(err);
+        }
+        self.add_dependency_impl(prec, succ);
+        Ok(())
+    }
+
+    /// Returns the error which adding a dependency from `prec` to `succ` would cause, if any.
+    fn check_dependency(&self, prec: &T, succ: &T) -> Option<CycleError<T>> {
+        CycleError::for_dependency(prec, succ, |item| self.top.get(item).map(|dep| &dep.succ))
+    }
+
+    /// Removes the two elements' dependency, keeping both elements.
+    ///
+    /// If the dependency was registered, `true` is returned. Otherwise `false` is returned.
+    pub fn remove_dependency(&mut self, prec: &T, succ: &T) -> bool {
+        if !self
+            .top
+            .get_mut(prec)
+            .map_or(false, |dep| dep.succ.remove(succ))
+        {
+            return false;
+        }
+        let dep = self.top.get_mut(succ).unwrap();
+        let _ = dep.prec.remove(prec);
+        if dep.prec.is_empty() {
+            self.push_ready(succ.clone());
+        }
+        true
+    }
+
+    /// Removes an element together with all dependencies from or to it, whether or not it is
+    /// depended on by other elements.
+    ///
+    /// If the `TopologicalSort` had this element present, `true` is returned. Otherwise `false`
+    /// is returned.
+    pub fn remove_node(&mut self, elt: &T) -> bool {
+        let dep = match self.top.get(elt) {
+            Some(dep) => dep,
+            None => return false,
+        };
+        if dep.prec.is_empty() {
+            self.remove_ready(elt);
+        }
+        let dep = self.top.remove(elt).unwrap();
+        for p in &dep.prec {
+            if let Some(x) = self.top.get_mut(p) {
+                let _ = x.succ.remove(elt);
+            }
+        }
+        self.release(elt, &dep.succ);
+        true
+    }
+
+    /// Removes an element together with all elements which depend on it, directly or
+    /// indirectly, and returns the latter, or `None` if the `TopologicalSort` does not contain
+    /// the element.
+    ///
+    /// The elements which do not depend on it are kept and can still be sorted.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("compile", "test");
+    /// ts.add_dependency("test", "release");
+    /// ts.add_dependency("lint", "release");
+    /// ts.add_dependency("compile", "docs");
+    /// let mut skipped = ts.remove_with_dependents(&"test").unwrap();
+    /// skipped.sort();
+    /// assert_eq!(vec!["release"], skipped);
+    /// assert_eq!(3, ts.len());
+    /// # }
+    /// ```
+    pub fn remove_with_dependents(&mut self, elt: &T) -> Option<Vec<T>> {
+        if !self.top.contains_key(elt) {
+            return None;
+        }
+        let dependents = self
+            .descendants(elt)
+            .into_iter()
+            .filter(|&item| item != elt)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let _ = self.remove_node(elt);
+        for item in &dependents {
+            let _ = self.remove_node(item);
+        }
+        Some(dependents)
+    }
+
     /// Registers a dependency link.
     pub fn add_link(&mut self, link: DependencyLink<T>) {
         self.add_dependency(link.prec, link.succ)
@@ -157,20 +527,62 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
     {
         match self.top.entry(elt.into()) {
             Entry::Vacant(e) => {
-                let dep = Dependency::new();
+                let dep = Dependency::new(self.next_seq);
+                self.next_seq += 1;
+                let elt = e.key().clone();
                 let _ = e.insert(dep);
+                self.push_ready(elt);
                 true
             }
             Entry::Occupied(_) => false,
         }
     }
 
+    /// Inserts an element with the given priority, without adding any dependencies from or to
+    /// it.
+    ///
+    /// If the `TopologicalSort` already had this element present, its priority is updated and
+    /// `false` is returned. Otherwise `true` is returned.
+    ///
+    /// Priorities are only taken into account with [`SortOrder::priority`].
+    ///
+    /// [`SortOrder::priority`]: struct.SortOrder.html#method.priority
+    pub fn insert_with_priority<U>(&mut self, elt: U, priority: i64) -> bool
+    where
+        U: Into<T>,
+    {
+        let elt = elt.into();
+        let inserted = self.insert(elt.clone());
+        let _ = self.set_priority(&elt, priority);
+        inserted
+    }
+
+    /// Sets the priority of an element.
+    ///
+    /// Returns `false` if the `TopologicalSort` does not contain the element.
+    pub fn set_priority(&mut self, elt: &T, priority: i64) -> bool {
+        match self.top.get_mut(elt) {
+            Some(dep) => dep.priority = priority,
+            None => return false,
+        }
+        let (order, top) = (&self.order, &self.top);
+        let _ = self.ready.update(elt, |a, b| order.compare(top, a, b));
+        true
+    }
+
+    /// Returns the priority of an element, or `None` if the `TopologicalSort` does not contain
+    /// it.
+    pub fn priority(&self, elt: &T) -> Option<i64> {
+        self.top.get(elt).map(|dep| dep.priority)
+    }
+
     /// Removes the item that is not depended on by any other items and returns it, or `None` if
     /// there is no such item.
     ///
-    /// If `pop` returns `None` and `len` is not 0, there is cyclic dependencies.
+    /// If `pop` returns `None` and `len` is not 0, there is cyclic dependencies. Use
+    /// [`find_cycle`](#method.find_cycle) to find out which items form the cycle.
     pub fn pop(&mut self) -> Option<T> {
-        self.peek().map(T::clone).map(|key| {
+        self.take_ready().map(|key| {
             let _ = self.remove(&key);
             key
         })
@@ -179,14 +591,12 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
     /// Removes all items that are not depended on by any other items and returns it, or empty
     /// vector if there are no such items.
     ///
+    /// The items are returned in the order set by [`set_order`](#method.set_order).
+    ///
     /// If `pop_all` returns an empty vector and `len` is not 0, there is cyclic dependencies.
     pub fn pop_all(&mut self) -> Vec<T> {
-        let keys = self
-            .top
-            .iter()
-            .filter(|&(_, v)| v.num_prec == 0)
-            .map(|(k, _)| k.clone())
-            .collect::<Vec<_>>();
+        let mut keys = self.ready.take_all();
+        keys.sort_by(|a, b| self.order.compare(&self.top, a, b));
         for k in &keys {
             let _ = self.remove(k);
         }
@@ -196,33 +606,515 @@ impl<T: Hash + Eq + Clone> TopologicalSort<T> {
     /// Return a reference to the first item that does not depend on any other items, or `None` if
     /// there is no such item.
     pub fn peek(&self) -> Option<&T> {
-        self.top
-            .iter()
-            .filter(|&(_, v)| v.num_prec == 0)
-            .map(|(k, _)| k)
-            .next()
+        self.ready.peek()
     }
 
     /// Return a vector of references to all items that do not depend on any other items, or an
     /// empty vector if there are no such items.
+    ///
+    /// The items are returned in the order set by [`set_order`](#method.set_order).
     pub fn peek_all(&self) -> Vec<&T> {
-        self.top
-            .iter()
-            .filter(|&(_, v)| v.num_prec == 0)
-            .map(|(k, _)| k)
-            .collect::<Vec<_>>()
+        let mut keys = Vec::with_capacity(self.ready.len());
+        keys.extend(self.ready.iter());
+        keys.sort_by(|a, b| self.order.compare(&self.top, a, b));
+        keys
+    }
+
+    /// Returns an iterator over the items in topological order, without removing them.
+    ///
+    /// Items which are part of a cycle, or which depend on such items, are not returned.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// Returns all items in topological order, without removing them.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// ts.add_dependency("stdio.h", "hello_world.o");
+    /// assert_eq!(Ok(vec!["stdio.h", "hello_world.o", "hello_world"]), ts.sorted());
+    /// assert_eq!(3, ts.len());
+    ///
+    /// ts.add_dependency("hello_world", "stdio.h");
+    /// assert_eq!(3, ts.sorted().unwrap_err().cycle().len());
+    /// # }
+    /// ```
+    pub fn sorted(&self) -> Result<Vec<T>, CycleError<T>> {
+        let sorted = self.iter().collect::<Vec<_>>();
+        if sorted.len() < self.len() {
+            return Err(self.cycle_error(&sorted));
+        }
+        Ok(sorted.into_iter().cloned().collect())
+    }
+
+    /// Checks that `order` contains every item exactly once, and no item before one it depends
+    /// on.
+    ///
+    /// Returns the first violation found: unknown and duplicate items in the order they appear,
+    /// then missing items, then dependencies in the order their `succ` appears.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::{OrderViolation, TopologicalSort};
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("hello_world.c", "hello_world.o");
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// assert_eq!(Ok(()), ts.is_valid_order(&["hello_world.c", "hello_world.o", "hello_world"]));
+    /// assert_eq!(
+    ///     Err(OrderViolation::Dependency { prec: "hello_world.c", succ: "hello_world.o" }),
+    ///     ts.is_valid_order(&["hello_world.o", "hello_world.c", "hello_world"]),
+    /// );
+    /// # }
+    /// ```
+    pub fn is_valid_order(&self, order: &[T]) -> Result<(), OrderViolation<T>> {
+        let mut position = HashMap::with_capacity(order.len());
+        for (i, item) in order.iter().enumerate() {
+            if !self.top.contains_key(item) {
+                return Err(OrderViolation::Unknown(item.clone()));
+            }
+            if position.insert(item, i).is_some() {
+                return Err(OrderViolation::Duplicate(item.clone()));
+            }
+        }
+        if position.len() < self.len() {
+            let missing = self
+                .top
+                .iter()
+                .filter(|(item, _)| !position.contains_key(item))
+                .min_by_key(|(_, dep)| dep.seq)
+                .unwrap();
+            return Err(OrderViolation::Missing(missing.0.clone()));
+        }
+
+        for (i, succ) in order.iter().enumerate() {
+            let prec = self.top[succ]
+                .prec
+                .iter()
+                .filter(|prec| position[prec] >= i)
+                .min_by_key(|prec| position[prec]);
+            if let Some(prec) = prec {
+                return Err(OrderViolation::Dependency {
+                    prec: prec.clone(),
+                    succ: succ.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns all items grouped in the layers `pop_all` would return, without removing them.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    pub fn layers(&self) -> Result<Vec<Vec<T>>, CycleError<T>> {
+        let mut iter = self.iter();
+        let mut layers = Vec::new();
+        let mut sorted = Vec::with_capacity(self.len());
+        loop {
+            let layer = iter.next_layer();
+            if layer.is_empty() {
+                break;
+            }
+            sorted.extend(layer.iter().cloned());
+            layers.push(layer);
+        }
+        if sorted.len() < self.len() {
+            return Err(self.cycle_error(&sorted));
+        }
+        Ok(layers
+            .into_iter()
+            .map(|layer| layer.into_iter().cloned().collect())
+            .collect())
+    }
+
+    /// Returns the index of the layer of `layers` containing the element, without computing the
+    /// other layers.
+    ///
+    /// This is the number of items on the longest chain of dependencies leading to the
+    /// element. Returns `None` if the `TopologicalSort` does not contain the element, or if it
+    /// cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("hello_world.c", "hello_world.o");
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// ts.add_dependency("stdio.h", "hello_world");
+    /// assert_eq!(Some(2), ts.layer_of(&"hello_world"));
+    /// assert_eq!(Some(0), ts.layer_of(&"stdio.h"));
+    /// assert_eq!(None, ts.layer_of(&"hello_world.h"));
+    /// # }
+    /// ```
+    pub fn layer_of(&self, elt: &T) -> Option<usize> {
+        let (elt, _) = self.top.get_key_value(elt)?;
+        // Items are `None` from the time their predecessors are visited until their layer is
+        // known, so finding one among the predecessors of an item means there is a cycle.
+        let mut layer = HashMap::<&T, Option<usize>>::new();
+        let mut stack = vec![elt];
+        while let Some(&item) = stack.last() {
+            if let Some(Some(_)) = layer.get(item) {
+                let _ = stack.pop();
+                continue;
+            }
+            let _ = layer.insert(item, None);
+            let mut depth = Some(0);
+            for prec in &self.top[item].prec {
+                match layer.get(prec) {
+                    Some(&Some(d)) => depth = depth.map(|depth| depth.max(d + 1)),
+                    Some(None) => return None,
+                    None => {
+                        stack.push(prec);
+                        depth = None;
+                    }
+                }
+            }
+            if depth.is_some() {
+                let _ = layer.insert(item, depth);
+                let _ = stack.pop();
+            }
+        }
+        layer[elt]
+    }
+
+    /// Returns the index of the layer of `layers` containing each item, without removing them.
+    ///
+    /// Every item is in the layer right after the last of the items it depends on, so it comes
+    /// as early as possible. Returns an error if some items cannot be sorted because of cyclic
+    /// dependencies.
+    pub fn layer_map(&self) -> Result<HashMap<T, usize>, CycleError<T>> {
+        let order = self.iter().collect::<Vec<_>>();
+        if order.len() < self.len() {
+            return Err(self.cycle_error(&order));
+        }
+        let mut layer = HashMap::<&T, usize>::with_capacity(order.len());
+        for &item in &order {
+            let depth = self.top[item]
+                .prec
+                .iter()
+                .map(|prec| layer[prec] + 1)
+                .max()
+                .unwrap_or(0);
+            let _ = layer.insert(item, depth);
+        }
+        Ok(layer
+            .into_iter()
+            .map(|(item, depth)| (item.clone(), depth))
+            .collect())
+    }
+
+    /// Returns the index of the latest layer each item can be placed in without increasing the
+    /// number of layers, without removing them.
+    ///
+    /// Every item is in the layer right before the first of the items depending on it, and
+    /// items nothing depends on are in the last layer, so it comes as late as possible. Returns
+    /// an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("hello_world.c", "hello_world.o");
+    /// ts.add_dependency("hello_world.o", "hello_world");
+    /// ts.add_dependency("stdio.h", "hello_world");
+    /// assert_eq!(0, ts.layer_map().unwrap()["stdio.h"]);
+    /// assert_eq!(1, ts.latest_layer_map().unwrap()["stdio.h"]);
+    /// # }
+    /// ```
+    pub fn latest_layer_map(&self) -> Result<HashMap<T, usize>, CycleError<T>> {
+        // Items sorted by their earliest layer come after all the items they depend on.
+        let earliest = self.layer_map()?;
+        let last = earliest.values().max().cloned().unwrap_or(0);
+        let mut order = self.top.keys().collect::<Vec<_>>();
+        order.sort_by_key(|&item| earliest[item]);
+
+        let mut layer = HashMap::<&T, usize>::with_capacity(order.len());
+        for &item in order.iter().rev() {
+            let latest = self.top[item]
+                .succ
+                .iter()
+                .map(|succ| layer[succ] - 1)
+                .min()
+                .unwrap_or(last);
+            let _ = layer.insert(item, latest);
+        }
+        Ok(layer
+            .into_iter()
+            .map(|(item, latest)| (item.clone(), latest))
+            .collect())
+    }
+
+    /// Removes all items and returns them in topological order.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies. Unlike
+    /// collecting the items popped by the `Iterator` implementation, this can not silently
+    /// drop the items which are left.
+    pub fn try_into_sorted_vec(self) -> Result<Vec<T>, CycleError<T>> {
+        self.into_try_iter().collect()
+    }
+
+    /// Returns an iterator which removes the items in topological order.
+    ///
+    /// Once no item can be removed anymore, the iterator yields an error if there are items left
+    /// because of cyclic dependencies, and then ends.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("stone", "sharp");
+    /// ts.add_dependency("sharp", "water");
+    /// ts.add_dependency("water", "sharp");
+    /// let mut iter = ts.into_try_iter();
+    /// assert_eq!(Some(Ok("stone")), iter.next());
+    /// assert_eq!(2, iter.next().unwrap().unwrap_err().cycle().len());
+    /// assert_eq!(None, iter.next());
+    /// # }
+    /// ```
+    pub fn into_try_iter(self) -> TryIter<T> {
+        TryIter::new(self)
+    }
+
+    /// Converts the remaining items into an error describing their cyclic dependencies.
+    fn into_cycle_error(self) -> CycleError<T> {
+        let cycle = self.find_cycle().unwrap_or_default();
+        CycleError::new(self.top.into_keys().collect(), cycle)
+    }
+
+    /// Builds the error for the items which are not in `sorted`.
+    fn cycle_error(&self, sorted: &[&T]) -> CycleError<T> {
+        let sorted = sorted.iter().cloned().collect::<HashSet<_>>();
+        let unsorted = self
+            .top
+            .keys()
+            .filter(|k| !sorted.contains(k))
+            .cloned()
+            .collect();
+        CycleError::new(unsorted, self.find_cycle().unwrap_or_default())
+    }
+
+    /// Returns one cycle among the remaining items, or `None` if there is no cycle.
+    ///
+    /// The items are returned in dependency order: each item is depended on by the item after
+    /// it, and the last item is depended on by the first one. A dependency of an item on itself
+    /// is returned as a cycle with a single item.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("a", "b");
+    /// ts.add_dependency("b", "c");
+    /// assert_eq!(None, ts.find_cycle());
+    /// ts.add_dependency("c", "b");
+    /// let cycle = ts.find_cycle().unwrap();
+    /// assert!(cycle == ["b", "c"] || cycle == ["c", "b"]);
+    /// # }
+    /// ```
+    pub fn find_cycle(&self) -> Option<Vec<T>> {
+        // `true` while the item is on the current path, `false` once it is fully explored.
+        let mut on_path = HashMap::<&T, bool>::new();
+        let mut path = Vec::<&T>::new();
+        let mut stack = Vec::new();
+
+        for start in self.top.keys() {
+            if on_path.contains_key(start) {
+                continue;
+            }
+            let _ = on_path.insert(start, true);
+            path.push(start);
+            stack.push(self.top[start].succ.iter());
+
+            while let Some(succs) = stack.last_mut() {
+                match succs.next() {
+                    Some(next) => match on_path.get(next) {
+                        Some(true) => {
+                            let pos = path.iter().position(|&p| p == next).unwrap();
+                            return Some(path[pos..].iter().map(|&p| p.clone()).collect());
+                        }
+                        Some(false) => {}
+                        None => {
+                            let _ = on_path.insert(next, true);
+                            path.push(next);
+                            stack.push(self.top[next].succ.iter());
+                        }
+                    },
+                    None => {
+                        let done = path.pop().unwrap();
+                        let _ = on_path.insert(done, false);
+                        let _ = stack.pop();
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns the strongly connected components of the dependency graph.
+    ///
+    /// Every item belongs to exactly one component. Items which depend on each other, directly
+    /// or indirectly, belong to the same component; all other items form a component of their
+    /// own. Components are returned in reverse topological order: each component appears before
+    /// the components it depends on.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<T>> {
+        // Iterative version of Tarjan's algorithm. `index` maps each visited item to its
+        // visitation index and its lowlink.
+        let mut index = HashMap::<&T, (usize, usize)>::new();
+        let mut stack = Vec::<&T>::new();
+        let mut on_stack = HashSet::<&T>::new();
+        let mut call_stack = Vec::new();
+        let mut components = Vec::new();
+
+        for start in self.top.keys() {
+            if index.contains_key(start) {
+                continue;
+            }
+            let start_index = index.len();
+            let _ = index.insert(start, (start_index, start_index));
+            stack.push(start);
+            let _ = on_stack.insert(start);
+            call_stack.push((start, self.top[start].succ.iter()));
+
+            while let Some((node, succs)) = call_stack.last_mut() {
+                let node = *node;
+                if let Some(next) = succs.next() {
+                    if let Some(&(next_index, _)) = index.get(next) {
+                        if on_stack.contains(next) {
+                            let low = &mut index.get_mut(node).unwrap().1;
+                            *low = (*low).min(next_index);
+                        }
+                    } else {
+                        let next_index = index.len();
+                        let _ = index.insert(next, (next_index, next_index));
+                        stack.push(next);
+                        let _ = on_stack.insert(next);
+                        call_stack.push((next, self.top[next].succ.iter()));
+                    }
+                    continue;
+                }
+
+                let _ = call_stack.pop();
+                let (node_index, node_low) = index[node];
+                if let Some(&(parent, _)) = call_stack.last() {
+                    let low = &mut index.get_mut(parent).unwrap().1;
+                    *low = (*low).min(node_low);
+                }
+                if node_index == node_low {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        let _ = on_stack.remove(member);
+                        component.push(member.clone());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Returns the condensation of the dependency graph, in which each strongly connected
+    /// component is collapsed into a single item.
+    ///
+    /// Items which depend on each other end up in the same item of the condensation, so the
+    /// condensation never contains cyclic dependencies and can always be sorted completely.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("libc", "libfoo");
+    /// ts.add_dependency("libfoo", "libbar");
+    /// ts.add_dependency("libbar", "libfoo");
+    /// ts.add_dependency("libbar", "app");
+    /// let mut condensed = ts.condense();
+    /// assert_eq!(Some(vec!["libc"]), condensed.pop());
+    /// assert_eq!(Some(vec!["libbar", "libfoo"]), condensed.pop().map(|mut v| { v.sort(); v }));
+    /// assert_eq!(Some(vec!["app"]), condensed.pop());
+    /// assert!(condensed.is_empty());
+    /// # }
+    /// ```
+    pub fn condense(&self) -> TopologicalSort<Vec<T>> {
+        let components = self.strongly_connected_components();
+        let mut component_of = HashMap::<&T, usize>::new();
+        for (i, component) in components.iter().enumerate() {
+            for item in component {
+                let _ = component_of.insert(item, i);
+            }
+        }
+
+        let mut links = HashSet::new();
+        for (item, dep) in &self.top {
+            let prec = component_of[item];
+            for succ in &dep.succ {
+                let succ = component_of[succ];
+                if prec != succ {
+                    let _ = links.insert((prec, succ));
+                }
+            }
+        }
+
+        let mut condensed = TopologicalSort::new();
+        for component in &components {
+            let _ = condensed.insert(component.clone());
+        }
+        for (prec, succ) in links {
+            condensed.add_dependency(components[prec].clone(), components[succ].clone());
+        }
+        condensed
     }
 
+    /// Removes the first item from the ready set without releasing its successors.
+    fn take_ready(&mut self) -> Option<T> {
+        let (order, top) = (&self.order, &self.top);
+        self.ready.pop(|a, b| order.compare(top, a, b))
+    }
+
+    /// Removes an item which is not in the ready set, releasing its successors.
     fn remove(&mut self, prec: &T) -> Option<Dependency<T>> {
         let result = self.top.remove(prec);
         if let Some(ref p) = result {
-            for s in &p.succ {
-                if let Some(y) = self.top.get_mut(s) {
-                    y.num_prec -= 1;
+            self.release(prec, &p.succ);
+        }
+        result
+    }
+
+    /// Removes the dependencies of `succ` on the removed item `prec`.
+    fn release(&mut self, prec: &T, succ: &HashSet<T>) {
+        for s in succ {
+            if let Some(y) = self.top.get_mut(s) {
+                let _ = y.prec.remove(prec);
+                if y.prec.is_empty() {
+                    self.push_ready(s.clone());
                 }
             }
         }
-        result
+    }
+
+    fn push_ready(&mut self, item: T) {
+        let (order, top) = (&self.order, &self.top);
+        self.ready.push(item, |a, b| order.compare(top, a, b));
+    }
+
+    fn remove_ready(&mut self, item: &T) {
+        let (order, top) = (&self.order, &self.top);
+        let _ = self.ready.remove(item, |a, b| order.compare(top, a, b));
     }
 }
 
@@ -285,9 +1177,18 @@ impl<T: Hash + Eq + Clone> Iterator for TopologicalSort<T> {
     }
 }
 
+impl<'a, T: Hash + Eq + Clone> IntoIterator for &'a TopologicalSort<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
 impl<T: fmt::Debug + Hash + Eq> fmt::Debug for Dependency<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "prec={}, succ={:?}", self.num_prec, self.succ)
+        write!(f, "prec={}, succ={:?}", self.prec.len(), self.succ)
     }
 }
 
@@ -299,8 +1200,9 @@ impl<T: fmt::Debug + Hash + Eq + Clone> fmt::Debug for TopologicalSort<T> {
 
 #[cfg(test)]
 mod test {
-    use super::TopologicalSort;
+    use super::{OrderViolation, SortOrder, TopologicalSort};
     use quickcheck_macros::quickcheck;
+    use std::collections::HashSet;
     use std::iter::FromIterator;
 
     #[test]
@@ -365,6 +1267,413 @@ mod test {
         check(&[], &mut ts);
     }
 
+    #[test]
+    fn peek_after_insert() {
+        let mut ts = TopologicalSort::<i32>::new();
+        assert!(ts.insert(1));
+        assert!(ts.insert(2));
+        assert!(ts.insert(3));
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        assert_eq!(vec![&1], ts.peek_all());
+        assert_eq!(Some(&1), ts.peek());
+        assert_eq!(vec![1, 2, 3], ts.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut ts = TopologicalSort::<&str>::with_order(SortOrder::insertion());
+        assert!(ts.insert("zlib"));
+        ts.add_dependency("openssl", "curl");
+        ts.add_dependency("zlib", "curl");
+        assert!(ts.insert("bzip2"));
+        ts.add_dependency("curl", "git");
+        assert_eq!(vec!["zlib", "openssl", "bzip2"], ts.pop_all());
+        assert_eq!(vec!["curl", "git"], ts.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ord_order() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(5, 2);
+        ts.add_dependency(4, 2);
+        ts.add_dependency(6, 1);
+        ts.add_dependency(3, 1);
+        ts.set_order(SortOrder::ord());
+        assert_eq!(vec![&3, &4, &5, &6], ts.peek_all());
+        assert_eq!(vec![3, 4, 5, 2, 6, 1], ts.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn comparator_order() {
+        let mut ts = TopologicalSort::<i32>::with_order(SortOrder::by(|a: &i32, b: &i32| {
+            (a % 2).cmp(&(b % 2))
+        }));
+        for i in 1..=6 {
+            assert!(ts.insert(i));
+        }
+        ts.add_dependency(5, 4);
+        assert_eq!(vec![2, 6, 1, 3, 5], ts.pop_all());
+        assert_eq!(vec![4], ts.pop_all());
+    }
+
+    #[test]
+    fn priority_order() {
+        let mut ts = TopologicalSort::<&str>::with_order(SortOrder::priority());
+        assert!(ts.insert_with_priority("lint", -1));
+        assert!(ts.insert_with_priority("compile", 10));
+        assert!(ts.insert("docs"));
+        ts.add_dependency("compile", "test");
+        ts.add_dependency("compile", "package");
+        assert!(ts.set_priority(&"package", 5));
+        assert!(!ts.insert_with_priority("docs", 1));
+        assert!(!ts.set_priority(&"deploy", 1));
+        assert_eq!(Some(5), ts.priority(&"package"));
+
+        assert_eq!(Some("compile"), ts.pop());
+        assert_eq!(vec![&"package", &"docs", &"test", &"lint"], ts.peek_all());
+        assert!(ts.set_priority(&"lint", 100));
+        assert_eq!(Some(&"lint"), ts.peek());
+    }
+
+    #[test]
+    fn remove_dependency() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(3, 2);
+        ts.add_dependency(2, 4);
+        assert!(ts.remove_dependency(&1, &2));
+        assert!(!ts.remove_dependency(&1, &2));
+        assert!(!ts.remove_dependency(&2, &1));
+        assert!(!ts.remove_dependency(&5, &1));
+        assert_eq!(4, ts.len());
+        assert_eq!(vec![1, 3], {
+            let mut v = ts.pop_all();
+            v.sort();
+            v
+        });
+        assert!(ts.remove_dependency(&2, &4));
+        assert_eq!(vec![2, 4], {
+            let mut v = ts.pop_all();
+            v.sort();
+            v
+        });
+    }
+
+    #[test]
+    fn remove_node() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(4, 3);
+        ts.add_dependency(3, 3);
+        assert!(ts.remove_node(&3));
+        assert!(!ts.remove_node(&3));
+        assert!(ts.remove_node(&1));
+        assert_eq!(2, ts.len());
+        assert_eq!(vec![2, 4], {
+            let mut v = ts.pop_all();
+            v.sort();
+            v
+        });
+        assert!(ts.is_empty());
+    }
+
+    #[test]
+    fn remove_with_dependents() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 2);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(5, 4);
+        ts.add_dependency(5, 6);
+        ts.add_dependency(1, 7);
+
+        let mut skipped = ts.remove_with_dependents(&2).unwrap();
+        skipped.sort_unstable();
+        assert_eq!(vec![3, 4], skipped);
+        assert_eq!(None, ts.remove_with_dependents(&2));
+        assert_eq!(Some(vec![]), ts.remove_with_dependents(&6));
+        assert_eq!(
+            Ok(vec![vec![1, 5], vec![7]]),
+            ts.layers().map(|layers| {
+                layers
+                    .into_iter()
+                    .map(|mut layer| {
+                        layer.sort_unstable();
+                        layer
+                    })
+                    .collect::<Vec<_>>()
+            })
+        );
+    }
+
+    #[test]
+    fn ancestors_and_descendants() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(4, 3);
+        ts.add_dependency(3, 5);
+        ts.add_dependency(6, 6);
+        let sorted = |set: HashSet<&i32>| {
+            let mut v = set.into_iter().cloned().collect::<Vec<_>>();
+            v.sort_unstable();
+            v
+        };
+
+        assert_eq!(vec![1, 2, 4], sorted(ts.ancestors(&3)));
+        assert_eq!(vec![3, 5], sorted(ts.descendants(&2)));
+        assert!(ts.ancestors(&1).is_empty());
+        assert!(ts.descendants(&7).is_empty());
+        assert_eq!(vec![6], sorted(ts.descendants(&6)));
+
+        assert!(ts.is_reachable(&1, &5));
+        assert!(!ts.is_reachable(&5, &1));
+        assert!(!ts.is_reachable(&1, &4));
+        assert!(!ts.is_reachable(&1, &1));
+        assert!(ts.is_reachable(&6, &6));
+    }
+
+    #[test]
+    fn subgraph_for() {
+        let mut ts = TopologicalSort::<i32>::with_order(SortOrder::insertion());
+        ts.add_dependency(7, 11);
+        ts.add_dependency(7, 8);
+        ts.add_dependency(5, 11);
+        ts.add_dependency(3, 8);
+        ts.add_dependency(3, 10);
+        ts.add_dependency(11, 2);
+        ts.add_dependency(11, 9);
+        ts.add_dependency(11, 10);
+        ts.add_dependency(8, 9);
+        assert!(ts.set_priority(&5, 3));
+
+        let mut sub = ts.subgraph_for(&[2, 8, 42]);
+        assert_eq!(6, sub.len());
+        assert!(sub.contains_dependency(&3, &8));
+        assert!(sub.contains_dependency(&7, &11));
+        assert!(!sub.contains(&9));
+        assert!(!sub.contains(&10));
+        assert_eq!(Some(3), sub.priority(&5));
+        assert_eq!(vec![7, 5, 3], sub.pop_all());
+        assert_eq!(vec![11, 8], sub.pop_all());
+        assert_eq!(vec![2], sub.pop_all());
+
+        assert!(ts.subgraph_for(&[]).is_empty());
+        assert_eq!(8, ts.len());
+    }
+
+    #[test]
+    fn neighbors() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 3);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 4);
+        assert!(ts.insert(5));
+
+        assert!(ts.contains(&5));
+        assert!(!ts.contains(&6));
+        assert!(ts.contains_dependency(&1, &3));
+        assert!(!ts.contains_dependency(&3, &1));
+        assert!(!ts.contains_dependency(&1, &4));
+        assert_eq!(Some(2), ts.in_degree(&3));
+        assert_eq!(Some(1), ts.out_degree(&3));
+        assert_eq!(Some(0), ts.out_degree(&5));
+        assert_eq!(None, ts.in_degree(&6));
+        assert_eq!(Some(&[4].iter().cloned().collect()), ts.successors(&3));
+
+        assert_eq!(Some(1), ts.pop_all().into_iter().find(|&x| x == 1));
+        assert_eq!(Some(0), ts.in_degree(&3));
+        assert!(ts.predecessors(&3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sorted_keeps_items() {
+        let mut ts = TopologicalSort::<i32>::with_order(SortOrder::ord());
+        ts.add_dependency(7, 11);
+        ts.add_dependency(7, 8);
+        ts.add_dependency(5, 11);
+        ts.add_dependency(3, 8);
+        ts.add_dependency(3, 10);
+        ts.add_dependency(11, 2);
+        ts.add_dependency(11, 9);
+        ts.add_dependency(11, 10);
+        ts.add_dependency(8, 9);
+
+        assert_eq!(
+            vec![&3, &5, &7, &8, &11, &2, &9, &10],
+            ts.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(Ok(vec![3, 5, 7, 8, 11, 2, 9, 10]), ts.sorted());
+        assert_eq!(
+            Ok(vec![vec![3, 5, 7], vec![8, 11], vec![2, 9, 10]]),
+            ts.layers()
+        );
+        assert_eq!(8, ts.len());
+        assert_eq!(vec![3, 5, 7], ts.pop_all());
+    }
+
+    #[test]
+    fn is_valid_order() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 3);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 4);
+        assert!(ts.insert(5));
+
+        assert_eq!(Ok(()), ts.is_valid_order(&[5, 2, 1, 3, 4]));
+        assert_eq!(Ok(()), ts.is_valid_order(&ts.sorted().unwrap()));
+        assert_eq!(
+            Err(OrderViolation::Unknown(6)),
+            ts.is_valid_order(&[1, 2, 3, 4, 6, 5])
+        );
+        assert_eq!(
+            Err(OrderViolation::Duplicate(1)),
+            ts.is_valid_order(&[1, 2, 1, 3, 4])
+        );
+        assert_eq!(
+            Err(OrderViolation::Missing(4)),
+            ts.is_valid_order(&[1, 2, 3, 5])
+        );
+        assert_eq!(
+            Err(OrderViolation::Dependency { prec: 2, succ: 3 }),
+            ts.is_valid_order(&[1, 3, 4, 2, 5])
+        );
+        assert_eq!(
+            Err(OrderViolation::Dependency { prec: 2, succ: 3 }),
+            ts.is_valid_order(&[5, 3, 2, 1, 4])
+        );
+
+        ts.add_dependency(4, 1);
+        assert!(ts.is_valid_order(&[1, 2, 3, 4, 5]).is_err());
+
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 1);
+        assert!(ts.sorted().is_err());
+        assert_eq!(
+            Err(OrderViolation::Dependency { prec: 1, succ: 1 }),
+            ts.is_valid_order(&[1])
+        );
+    }
+
+    #[test]
+    fn layer_map() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(5, 4);
+        ts.add_dependency(1, 6);
+        assert!(ts.insert(7));
+
+        let layers = ts.layer_map().unwrap();
+        for (i, layer) in ts.layers().unwrap().iter().enumerate() {
+            for item in layer {
+                assert_eq!(i, layers[item]);
+                assert_eq!(Some(i), ts.layer_of(item));
+            }
+        }
+        assert_eq!(None, ts.layer_of(&8));
+
+        let latest = ts.latest_layer_map().unwrap();
+        let expected = [(1, 0), (2, 1), (3, 2), (4, 3), (5, 2), (6, 3), (7, 3)];
+        assert_eq!(latest, expected.iter().cloned().collect());
+        assert_eq!(7, ts.len());
+
+        ts.add_dependency(4, 2);
+        assert_eq!(Some(0), ts.layer_of(&5));
+        assert_eq!(Some(1), ts.layer_of(&6));
+        assert_eq!(None, ts.layer_of(&4));
+        assert_eq!(None, ts.layer_of(&3));
+        assert!(ts.layer_map().is_err());
+        assert!(ts.latest_layer_map().is_err());
+
+        let empty = TopologicalSort::<i32>::new();
+        assert!(empty.layer_map().unwrap().is_empty());
+        assert!(empty.latest_layer_map().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sorted_cyclic() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 2);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(5, 4);
+
+        let mut iter = ts.iter().cloned().collect::<Vec<_>>();
+        iter.sort_unstable();
+        assert_eq!(vec![1, 5], iter);
+
+        let err = ts.sorted().unwrap_err();
+        let mut unsorted = err.unsorted().to_vec();
+        unsorted.sort_unstable();
+        assert_eq!(vec![2, 3, 4], unsorted);
+        let mut cycle = err.cycle().to_vec();
+        cycle.sort_unstable();
+        assert_eq!(vec![2, 3], cycle);
+        assert_eq!(err, ts.layers().unwrap_err());
+    }
+
+    #[test]
+    fn try_into_sorted_vec() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        assert_eq!(Ok(vec![1, 2, 3]), ts.clone().try_into_sorted_vec());
+
+        ts.add_dependency(3, 4);
+        ts.add_dependency(4, 3);
+        let err = ts.clone().try_into_sorted_vec().unwrap_err();
+        let mut unsorted = err.into_unsorted();
+        unsorted.sort_unstable();
+        assert_eq!(vec![3, 4], unsorted);
+
+        let results = ts.into_try_iter().collect::<Vec<_>>();
+        assert_eq!(3, results.len());
+        assert_eq!(Ok(1), results[0]);
+        assert_eq!(Ok(2), results[1]);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn try_add_dependency() {
+        let mut ts = TopologicalSort::<&str>::new();
+        assert!(ts.try_add_dependency("stone", "sharp").is_ok());
+        assert!(ts.try_add_dependency("bucket", "hole").is_ok());
+        assert!(ts.try_add_dependency("hole", "straw").is_ok());
+        assert!(ts.try_add_dependency("straw", "axe").is_ok());
+        assert!(ts.try_add_dependency("axe", "sharp").is_ok());
+        assert!(ts.try_add_dependency("sharp", "water").is_ok());
+        assert!(ts.try_add_dependency("water", "pump").is_ok());
+
+        let err = ts.try_add_dependency("water", "bucket").unwrap_err();
+        assert_eq!(
+            &["water", "bucket", "hole", "straw", "axe", "sharp"],
+            err.cycle()
+        );
+        let mut unsorted = err.into_unsorted();
+        unsorted.sort_unstable();
+        assert_eq!(
+            vec!["axe", "bucket", "hole", "pump", "sharp", "straw", "water"],
+            unsorted
+        );
+        assert!(!ts.contains_dependency(&"water", &"bucket"));
+        assert_eq!(Some(0), ts.in_degree(&"bucket"));
+
+        let err = ts.try_add_dependency("axe", "axe").unwrap_err();
+        assert_eq!(&["axe"], err.cycle());
+        let mut unsorted = err.into_unsorted();
+        unsorted.sort_unstable();
+        assert_eq!(vec!["axe", "pump", "sharp", "water"], unsorted);
+        assert!(!ts.contains_dependency(&"axe", &"axe"));
+        assert!(ts.try_add_dependency("stone", "pump").is_ok());
+        assert_eq!(8, ts.sorted().unwrap().len());
+    }
+
     #[test]
     fn cyclic_deadlock() {
         let mut ts = TopologicalSort::new();
@@ -381,11 +1690,88 @@ mod test {
         println!("{:?}", ts);
     }
 
+    #[test]
+    fn find_cycle() {
+        let mut ts = TopologicalSort::<&str>::new();
+        ts.add_dependency("stone", "sharp");
+        ts.add_dependency("bucket", "hole");
+        ts.add_dependency("hole", "straw");
+        ts.add_dependency("straw", "axe");
+        ts.add_dependency("axe", "sharp");
+        ts.add_dependency("sharp", "water");
+        assert_eq!(None, ts.find_cycle());
+
+        ts.add_dependency("water", "bucket");
+        let mut cycle = ts.find_cycle().unwrap();
+        let start = cycle.iter().position(|&x| x == "sharp").unwrap();
+        cycle.rotate_left(start);
+        assert_eq!(
+            vec!["sharp", "water", "bucket", "hole", "straw", "axe"],
+            cycle
+        );
+
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 1);
+        assert_eq!(Some(vec![1]), ts.find_cycle());
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 1);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(4, 5);
+        ts.add_dependency(5, 4);
+        ts.add_dependency(6, 6);
+        assert!(ts.insert(7));
+
+        let mut components = ts
+            .strongly_connected_components()
+            .into_iter()
+            .map(|mut c| {
+                c.sort();
+                c
+            })
+            .collect::<Vec<_>>();
+        let pos = |x: i32| components.iter().position(|c| c.contains(&x)).unwrap();
+        assert!(pos(4) < pos(1));
+        components.sort();
+        assert_eq!(
+            vec![vec![1, 2, 3], vec![4, 5], vec![6], vec![7]],
+            components
+        );
+    }
+
+    #[test]
+    fn condense_cyclic_deadlock() {
+        let mut ts = TopologicalSort::<&str>::new();
+        ts.add_dependency("stone", "sharp");
+        ts.add_dependency("bucket", "hole");
+        ts.add_dependency("hole", "straw");
+        ts.add_dependency("straw", "axe");
+        ts.add_dependency("axe", "sharp");
+        ts.add_dependency("sharp", "water");
+        ts.add_dependency("water", "bucket");
+
+        let mut condensed = ts.condense();
+        assert_eq!(2, condensed.len());
+        assert_eq!(Some(vec!["stone"]), condensed.pop());
+        let mut cycle = condensed.pop().unwrap();
+        cycle.sort_unstable();
+        assert_eq!(
+            vec!["axe", "bucket", "hole", "sharp", "straw", "water"],
+            cycle
+        );
+        assert!(condensed.is_empty());
+    }
+
     #[quickcheck]
     fn topo_test_quickcheck(n: usize, edges: Vec<(usize, usize)>) {
         use std::collections::{HashMap, HashSet};
 
-        let n = n.max(1).min(1000);
+        let n = n.clamp(1, 1000);
         let mut marked = vec![false; n];
         let edges = edges
             .into_iter()
@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::Dependency;
+
+/// The order in which a `TopologicalSort` returns items that are ready at the same time.
+///
+/// Except for [`arbitrary`](#method.arbitrary), every order is deterministic: items which compare
+/// equal are returned in the order in which they were first registered.
+pub struct SortOrder<T> {
+    kind: Kind<T>,
+}
+
+enum Kind<T> {
+    Arbitrary,
+    Insertion,
+    Priority,
+    Custom(Arc<dyn Compare<T> + Send + Sync>),
+}
+
+trait Compare<T> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
+}
+
+struct ByOrd;
+
+impl<T: Ord> Compare<T> for ByOrd {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+struct ByFn<F>(F);
+
+impl<T, F: Fn(&T, &T) -> Ordering> Compare<T> for ByFn<F> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+struct ByKeyRev<F, K>(F, PhantomData<fn() -> K>);
+
+impl<T, K: Ord, F: Fn(&T) -> K> Compare<T> for ByKeyRev<F, K> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (self.0)(b).cmp(&(self.0)(a))
+    }
+}
+
+impl<T> SortOrder<T> {
+    /// Returns items in an unspecified order, which may change between runs.
+    ///
+    /// This is the default.
+    pub fn arbitrary() -> SortOrder<T> {
+        SortOrder {
+            kind: Kind::Arbitrary,
+        }
+    }
+
+    /// Returns items in the order in which they were first registered, either by `insert` or by
+    /// `add_dependency`.
+    pub fn insertion() -> SortOrder<T> {
+        SortOrder {
+            kind: Kind::Insertion,
+        }
+    }
+
+    /// Returns the smallest item first according to its `Ord` implementation.
+    ///
+    /// Popping items one by one then produces the lexicographically smallest topological order.
+    pub fn ord() -> SortOrder<T>
+    where
+        T: Ord,
+    {
+        SortOrder {
+            kind: Kind::Custom(Arc::new(ByOrd)),
+        }
+    }
+
+    /// Returns the smallest item first according to `compare`.
+    pub fn by<F>(compare: F) -> SortOrder<T>
+    where
+        F: Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    {
+        SortOrder {
+            kind: Kind::Custom(Arc::new(ByFn(compare))),
+        }
+    }
+
+    /// Returns the item with the highest priority first, where the priority of an item is given
+    /// by `key`.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::{SortOrder, TopologicalSort};
+    /// let mut ts = TopologicalSort::<&str>::with_order(SortOrder::by_priority(|s: &&str| s.len()));
+    /// ts.add_dependency("a", "ccc");
+    /// ts.add_dependency("bb", "ccc");
+    /// ts.add_dependency("dddd", "a");
+    /// assert_eq!(vec!["dddd", "bb", "a", "ccc"], ts.collect::<Vec<_>>());
+    /// # }
+    /// ```
+    pub fn by_priority<F, K>(key: F) -> SortOrder<T>
+    where
+        F: Fn(&T) -> K + Send + Sync + 'static,
+        K: Ord + 'static,
+    {
+        SortOrder {
+            kind: Kind::Custom(Arc::new(ByKeyRev(key, PhantomData))),
+        }
+    }
+
+    /// Returns the item with the highest priority first, where the priority of an item is the
+    /// one registered with `TopologicalSort::insert_with_priority` or
+    /// `TopologicalSort::set_priority`.
+    ///
+    /// Items without a registered priority have a priority of 0.
+    pub fn priority() -> SortOrder<T> {
+        SortOrder {
+            kind: Kind::Priority,
+        }
+    }
+
+    pub(crate) fn compare(&self, top: &HashMap<T, Dependency<T>>, a: &T, b: &T) -> Ordering
+    where
+        T: Hash + Eq,
+    {
+        let by_seq = || top[a].seq.cmp(&top[b].seq);
+        match self.kind {
+            Kind::Arbitrary => Ordering::Equal,
+            Kind::Insertion => by_seq(),
+            Kind::Priority => top[b].priority.cmp(&top[a].priority).then_with(by_seq),
+            Kind::Custom(ref compare) => compare.compare(a, b).then_with(by_seq),
+        }
+    }
+}
+
+impl<T> Default for SortOrder<T> {
+    fn default() -> SortOrder<T> {
+        SortOrder::arbitrary()
+    }
+}
+
+impl<T> Clone for SortOrder<T> {
+    fn clone(&self) -> SortOrder<T> {
+        let kind = match self.kind {
+            Kind::Arbitrary => Kind::Arbitrary,
+            Kind::Insertion => Kind::Insertion,
+            Kind::Priority => Kind::Priority,
+            Kind::Custom(ref compare) => Kind::Custom(Arc::clone(compare)),
+        };
+        SortOrder { kind }
+    }
+}
+
+impl<T> fmt::Debug for SortOrder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            Kind::Arbitrary => write!(f, "Arbitrary"),
+            Kind::Insertion => write!(f, "Insertion"),
+            Kind::Priority => write!(f, "Priority"),
+            Kind::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
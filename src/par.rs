@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use rayon::Scope;
+
+use crate::{CycleError, OnError, Outcome, Scheduler, TopologicalSort};
+
+struct State<T, O, E> {
+    scheduler: Scheduler<T>,
+    outcomes: HashMap<T, Outcome<O, E>>,
+    stopped: bool,
+}
+
+/// Runs the task of every item on the rayon thread pool, starting each one as soon as the tasks
+/// of the items it depends on have succeeded.
+///
+/// Returns the outcome of every item. The items depending on an item whose task failed are
+/// skipped; `on_error` decides whether the other items keep running. If some items can not be
+/// sorted because of cyclic dependencies, no task is run and an error is returned.
+///
+/// This function is only available with the `rayon` feature.
+///
+/// ```rust
+/// # extern crate topological_sort;
+/// # fn main() {
+/// use topological_sort::{par_execute, OnError, Outcome, TopologicalSort};
+/// let mut ts = TopologicalSort::<i32>::new();
+/// ts.add_dependency(1, 2);
+/// ts.add_dependency(2, 3);
+/// ts.add_dependency(1, 4);
+/// let outcomes = par_execute(ts, OnError::Continue, |&n| {
+///     if n == 2 { Err("failed") } else { Ok(n * 10) }
+/// }).unwrap();
+/// assert_eq!(Outcome::Completed(10), outcomes[&1]);
+/// assert_eq!(Outcome::Failed("failed"), outcomes[&2]);
+/// assert_eq!(Outcome::Skipped, outcomes[&3]);
+/// assert_eq!(Outcome::Completed(40), outcomes[&4]);
+/// # }
+/// ```
+pub fn par_execute<T, O, E, F>(
+    ts: TopologicalSort<T>,
+    on_error: OnError,
+    task: F,
+) -> Result<HashMap<T, Outcome<O, E>>, CycleError<T>>
+where
+    T: Hash + Eq + Clone + Send,
+    O: Send,
+    E: Send,
+    F: Fn(&T) -> Result<O, E> + Sync,
+{
+    let items = ts.iter().cloned().collect::<Vec<_>>();
+    if items.len() < ts.len() {
+        return Err(ts.into_cycle_error());
+    }
+
+    let state = Mutex::new(State {
+        scheduler: Scheduler::new(ts),
+        outcomes: HashMap::with_capacity(items.len()),
+        stopped: false,
+    });
+    rayon::scope(|scope| spawn_ready(scope, &state, &task, on_error));
+
+    let mut outcomes = state.into_inner().unwrap().outcomes;
+    for item in items {
+        let _ = outcomes.entry(item).or_insert(Outcome::Skipped);
+    }
+    Ok(outcomes)
+}
+
+fn spawn_ready<'s, T, O, E, F>(
+    scope: &Scope<'s>,
+    state: &'s Mutex<State<T, O, E>>,
+    task: &'s F,
+    on_error: OnError,
+) where
+    T: Hash + Eq + Clone + Send,
+    O: Send,
+    E: Send,
+    F: Fn(&T) -> Result<O, E> + Sync,
+{
+    let ready = {
+        let mut state = state.lock().unwrap();
+        if state.stopped {
+            return;
+        }
+        state.scheduler.take_all()
+    };
+
+    for item in ready {
+        scope.spawn(move |scope| {
+            // Items spawned together may only start after another one has failed.
+            {
+                let mut state = state.lock().unwrap();
+                if state.stopped {
+                    let _ = state.outcomes.insert(item, Outcome::Skipped);
+                    return;
+                }
+            }
+            let result = task(&item);
+            {
+                let mut state = state.lock().unwrap();
+                let outcome = match result {
+                    Ok(output) => {
+                        let _ = state.scheduler.complete(&item);
+                        Outcome::Completed(output)
+                    }
+                    Err(err) => {
+                        state.stopped |= on_error == OnError::Stop;
+                        for skipped in state.scheduler.fail(&item).unwrap_or_default() {
+                            let _ = state.outcomes.insert(skipped, Outcome::Skipped);
+                        }
+                        Outcome::Failed(err)
+                    }
+                };
+                let _ = state.outcomes.insert(item, outcome);
+            }
+            spawn_ready(scope, state, task, on_error);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::par_execute;
+    use crate::{OnError, Outcome, TopologicalSort};
+    use std::sync::Mutex;
+
+    #[test]
+    fn runs_after_dependencies() {
+        let mut ts = TopologicalSort::<i32>::new();
+        for i in 1..100 {
+            ts.add_dependency(i / 2, i);
+            ts.add_dependency(i / 3, i);
+        }
+        let finished = Mutex::new(Vec::new());
+        let outcomes = par_execute(ts.clone(), OnError::Stop, |&i| {
+            let finished_before = finished.lock().unwrap().clone();
+            for prec in ts.predecessors(&i).unwrap() {
+                assert!(finished_before.contains(prec));
+            }
+            finished.lock().unwrap().push(i);
+            Ok::<_, ()>(i)
+        })
+        .unwrap();
+        assert_eq!(100, outcomes.len());
+        assert!(outcomes.iter().all(|(i, o)| *o == Outcome::Completed(*i)));
+    }
+
+    #[test]
+    fn stop_on_error() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        let outcomes =
+            par_execute(ts, OnError::Stop, |&n| if n == 1 { Err(n) } else { Ok(()) }).unwrap();
+        assert_eq!(Outcome::Failed(1), outcomes[&1]);
+        assert_eq!(Outcome::Skipped, outcomes[&2]);
+        assert_eq!(Outcome::Skipped, outcomes[&3]);
+    }
+
+    #[test]
+    fn stop_skips_spawned_items() {
+        let mut ts = TopologicalSort::<i32>::new();
+        for i in 0..50 {
+            assert!(ts.insert(i));
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let started = Mutex::new(0);
+        let outcomes = pool
+            .install(|| {
+                par_execute(ts, OnError::Stop, |&n| {
+                    let mut started = started.lock().unwrap();
+                    *started += 1;
+                    if *started == 1 {
+                        Err(n)
+                    } else {
+                        Ok(())
+                    }
+                })
+            })
+            .unwrap();
+        assert_eq!(1, *started.lock().unwrap());
+        assert_eq!(50, outcomes.len());
+        let failed = outcomes
+            .values()
+            .filter(|o| matches!(o, Outcome::Failed(_)))
+            .count();
+        assert_eq!(1, failed);
+        let skipped = outcomes
+            .values()
+            .filter(|o| **o == Outcome::Skipped)
+            .count();
+        assert_eq!(49, skipped);
+    }
+
+    #[test]
+    fn cyclic() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 1);
+        let err =
+            par_execute(ts, OnError::Stop, |_| -> Result<(), ()> { unreachable!() }).unwrap_err();
+        assert_eq!(2, err.cycle().len());
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{CycleError, TopologicalSort};
+
+/// The SplitMix64 generator, which is small and good enough to shuffle items.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number below `n`.
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+}
+
+impl<T: Hash + Eq + Clone> TopologicalSort<T> {
+    /// Returns all items in a random topological order drawn from `seed`, without removing
+    /// them.
+    ///
+    /// At each step, the next item is picked uniformly among the items which are ready. The
+    /// same seed always gives the same order, as long as the same items and dependencies were
+    /// added in the same order. The orders are not uniformly distributed over all topological
+    /// orders: items which many others depend on come first less often than they would under
+    /// uniform sampling, as they compete with all other ready items.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("config", "db");
+    /// ts.add_dependency("config", "cache");
+    /// ts.add_dependency("db", "server");
+    /// ts.add_dependency("cache", "server");
+    /// let order = ts.random_order(42).unwrap();
+    /// assert_eq!("config", order[0]);
+    /// assert_eq!("server", order[3]);
+    /// assert_eq!(order, ts.random_order(42).unwrap());
+    /// # }
+    /// ```
+    pub fn random_order(&self, seed: u64) -> Result<Vec<T>, CycleError<T>> {
+        let mut rng = SplitMix64(seed);
+        let mut ready = self.ready.iter().collect::<Vec<_>>();
+        ready.sort_by_key(|&item| self.top[item].seq);
+        let mut num_prec = HashMap::new();
+        let mut sorted = Vec::with_capacity(self.len());
+        while !ready.is_empty() {
+            let item = ready.swap_remove(rng.below(ready.len()));
+            sorted.push(item);
+            let mut released = Vec::new();
+            for succ in &self.top[item].succ {
+                let n = num_prec
+                    .entry(succ)
+                    .or_insert_with(|| self.top[succ].prec.len());
+                *n -= 1;
+                if *n == 0 {
+                    released.push(succ);
+                }
+            }
+            released.sort_by_key(|&succ| self.top[succ].seq);
+            ready.extend(released);
+        }
+
+        if sorted.len() < self.len() {
+            return Err(self.cycle_error(&sorted));
+        }
+        Ok(sorted.into_iter().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SplitMix64;
+    use crate::TopologicalSort;
+    use std::collections::HashSet;
+
+    #[test]
+    fn split_mix_64() {
+        let mut rng = SplitMix64(1234567);
+        assert_eq!(6457827717110365317, rng.next_u64());
+        assert_eq!(3203168211198807973, rng.next_u64());
+        assert!((0..1000).all(|_| rng.below(7) < 7));
+    }
+
+    #[test]
+    fn random_order() {
+        let mut ts = TopologicalSort::<i32>::new();
+        for i in 0..5 {
+            ts.add_dependency(i, 10);
+        }
+        ts.add_dependency(10, 11);
+
+        let mut firsts = HashSet::new();
+        for seed in 0..50 {
+            let order = ts.random_order(seed).unwrap();
+            assert_eq!(order, ts.random_order(seed).unwrap());
+            assert_eq!(&[10, 11], &order[5..]);
+            let _ = firsts.insert(order[0]);
+        }
+        assert_eq!(5, firsts.len());
+
+        ts.add_dependency(11, 10);
+        let err = ts.random_order(0).unwrap_err();
+        let mut cycle = err.cycle().to_vec();
+        cycle.sort_unstable();
+        assert_eq!(vec![10, 11], cycle);
+    }
+}
@@ -0,0 +1,206 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The set of items which do not depend on any other items.
+///
+/// Items are stored in a binary min-heap, with an index from item to its position in the heap, so
+/// that arbitrary items can be removed in O(log n). The ordering of the heap is supplied by the
+/// caller on every operation, as it usually depends on state stored outside of the set.
+#[derive(Clone)]
+pub(crate) struct ReadySet<T> {
+    items: Vec<T>,
+    index: HashMap<T, usize>,
+}
+
+impl<T> Default for ReadySet<T> {
+    fn default() -> ReadySet<T> {
+        ReadySet {
+            items: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> ReadySet<T> {
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Iterates over the items in an unspecified order.
+    pub(crate) fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Returns the smallest item.
+    pub(crate) fn peek(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    /// Adds an item which is not already in the set.
+    pub(crate) fn push<F>(&mut self, item: T, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        debug_assert!(!self.index.contains_key(&item));
+        let pos = self.items.len();
+        let _ = self.index.insert(item.clone(), pos);
+        self.items.push(item);
+        self.sift_up(pos, &cmp);
+    }
+
+    /// Removes the smallest item and returns it.
+    pub(crate) fn pop<F>(&mut self, cmp: F) -> Option<T>
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0, &cmp))
+        }
+    }
+
+    /// Removes `item` from the set, returning whether it was present.
+    pub(crate) fn remove<F>(&mut self, item: &T, cmp: F) -> bool
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        match self.index.get(item) {
+            Some(&pos) => {
+                let _ = self.remove_at(pos, &cmp);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves `item` to its new place after its ordering has changed, returning whether it was
+    /// present.
+    pub(crate) fn update<F>(&mut self, item: &T, cmp: F) -> bool
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        match self.index.get(item) {
+            Some(&pos) => {
+                self.sift_down(pos, &cmp);
+                self.sift_up(pos, &cmp);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all items from the set and returns them in an unspecified order.
+    pub(crate) fn take_all(&mut self) -> Vec<T> {
+        self.index.clear();
+        std::mem::take(&mut self.items)
+    }
+
+    /// Restores the heap property after the ordering has changed.
+    pub(crate) fn rebuild<F>(&mut self, cmp: F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        for pos in (0..self.items.len() / 2).rev() {
+            self.sift_down(pos, &cmp);
+        }
+    }
+
+    fn remove_at<F>(&mut self, pos: usize, cmp: &F) -> T
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        let last = self.items.len() - 1;
+        self.swap(pos, last);
+        let item = self.items.pop().unwrap();
+        let _ = self.index.remove(&item);
+        if pos < self.items.len() {
+            self.sift_down(pos, cmp);
+            self.sift_up(pos, cmp);
+        }
+        item
+    }
+
+    fn sift_up<F>(&mut self, mut pos: usize, cmp: &F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if cmp(&self.items[pos], &self.items[parent]) != Ordering::Less {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down<F>(&mut self, mut pos: usize, cmp: &F)
+    where
+        F: Fn(&T, &T) -> Ordering,
+    {
+        loop {
+            let mut smallest = pos;
+            for child in &[2 * pos + 1, 2 * pos + 2] {
+                if *child < self.items.len()
+                    && cmp(&self.items[*child], &self.items[smallest]) == Ordering::Less
+                {
+                    smallest = *child;
+                }
+            }
+            if smallest == pos {
+                break;
+            }
+            self.swap(pos, smallest);
+            pos = smallest;
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        self.items.swap(a, b);
+        *self.index.get_mut(&self.items[a]).unwrap() = a;
+        *self.index.get_mut(&self.items[b]).unwrap() = b;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReadySet;
+
+    #[test]
+    fn remove_keeps_index() {
+        let cmp = i32::cmp;
+        let mut ready = ReadySet::default();
+        for i in &[3, 0, 4, 1, 2] {
+            ready.push(*i, cmp);
+        }
+        assert!(ready.remove(&1, cmp));
+        assert!(!ready.remove(&1, cmp));
+        assert!(ready.remove(&4, cmp));
+        assert!(ready.remove(&2, cmp));
+        assert_eq!(2, ready.len());
+        assert_eq!(Some(&0), ready.peek());
+        assert_eq!(Some(0), ready.pop(cmp));
+        assert_eq!(Some(3), ready.pop(cmp));
+        assert_eq!(None, ready.pop(cmp));
+    }
+
+    #[test]
+    fn rebuild() {
+        let mut ready = ReadySet::default();
+        for i in 0..10 {
+            ready.push(i, i32::cmp);
+        }
+        let rev = |a: &i32, b: &i32| b.cmp(a);
+        ready.rebuild(rev);
+        let mut popped = Vec::new();
+        while let Some(i) = ready.pop(rev) {
+            popped.push(i);
+        }
+        assert_eq!((0..10).rev().collect::<Vec<_>>(), popped);
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::TopologicalSort;
+
+/// Hands out the items of a `TopologicalSort` to workers as soon as they are ready.
+///
+/// Unlike `TopologicalSort::pop`, taking an item does not release the items depending on it:
+/// they only become ready once the item is marked as completed. This lets every item start as
+/// soon as its own dependencies are done, instead of waiting for a whole layer.
+///
+/// ```rust
+/// # extern crate topological_sort;
+/// # fn main() {
+/// use topological_sort::{Scheduler, TopologicalSort};
+/// let mut ts = TopologicalSort::<&str>::new();
+/// ts.add_dependency("fetch", "build");
+/// ts.add_dependency("build", "test");
+/// ts.add_dependency("fetch", "lint");
+/// let mut scheduler = Scheduler::new(ts);
+/// assert_eq!(Some("fetch"), scheduler.take());
+/// assert_eq!(None, scheduler.take());
+/// assert!(scheduler.complete(&"fetch"));
+/// let mut ready = scheduler.take_all();
+/// ready.sort();
+/// assert_eq!(vec!["build", "lint"], ready);
+/// assert!(scheduler.complete(&"build"));
+/// assert_eq!(Some("test"), scheduler.take());
+/// # }
+/// ```
+pub struct Scheduler<T> {
+    ts: TopologicalSort<T>,
+    in_flight: HashSet<T>,
+}
+
+impl<T: Hash + Eq + Clone> Scheduler<T> {
+    /// Creates a `Scheduler` handing out the items of `ts`.
+    pub fn new(ts: TopologicalSort<T>) -> Scheduler<T> {
+        Scheduler {
+            ts,
+            in_flight: HashSet::new(),
+        }
+    }
+
+    /// Returns the number of items which have not been completed yet, including the ones in
+    /// flight.
+    pub fn len(&self) -> usize {
+        self.ts.len()
+    }
+
+    /// Returns true if all items have been completed.
+    pub fn is_empty(&self) -> bool {
+        self.ts.is_empty()
+    }
+
+    /// Returns true if no item can be taken anymore, and none is in flight, but some items have
+    /// not been completed because of cyclic dependencies.
+    pub fn is_stalled(&self) -> bool {
+        self.in_flight.is_empty() && self.ts.peek().is_none() && !self.ts.is_empty()
+    }
+
+    /// Returns the items which have been taken but not completed yet.
+    pub fn in_flight(&self) -> &HashSet<T> {
+        &self.in_flight
+    }
+
+    /// Returns a reference to the next item which `take` would return, or `None` if no item is
+    /// ready.
+    pub fn peek(&self) -> Option<&T> {
+        self.ts.peek()
+    }
+
+    /// Marks a ready item as in flight and returns it, or `None` if no item is ready.
+    ///
+    /// The items depending on it stay blocked until it is completed.
+    pub fn take(&mut self) -> Option<T> {
+        let item = self.ts.take_ready()?;
+        let _ = self.in_flight.insert(item.clone());
+        Some(item)
+    }
+
+    /// Marks all ready items as in flight and returns them, or an empty vector if no item is
+    /// ready.
+    pub fn take_all(&mut self) -> Vec<T> {
+        let mut items = Vec::new();
+        while let Some(item) = self.take() {
+            items.push(item);
+        }
+        items
+    }
+
+    /// Marks an item in flight as completed, making the items depending on it ready once all
+    /// their other dependencies are completed.
+    ///
+    /// If the item was in flight, `true` is returned. Otherwise `false` is returned.
+    pub fn complete(&mut self, elt: &T) -> bool {
+        if !self.in_flight.remove(elt) {
+            return false;
+        }
+        let _ = self.ts.remove(elt);
+        true
+    }
+
+    /// Marks an item in flight as failed, and removes all items which depend on it, directly
+    /// or indirectly.
+    ///
+    /// Returns the removed items, which will never be taken, or `None` if the item was not in
+    /// flight. The items which do not depend on it can still be taken.
+    pub fn fail(&mut self, elt: &T) -> Option<Vec<T>> {
+        if !self.in_flight.remove(elt) {
+            return None;
+        }
+        self.ts.remove_with_dependents(elt)
+    }
+}
+
+/// The outcome of running the task of an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome<O, E> {
+    /// The task succeeded.
+    Completed(O),
+    /// The task failed.
+    Failed(E),
+    /// The task was not run, because one of its dependencies failed or execution was stopped.
+    Skipped,
+}
+
+/// What to do with the remaining items once the task of an item has failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OnError {
+    /// Do not start any new task, but let the tasks already running finish.
+    Stop,
+    /// Keep running the tasks of all items which do not depend on a failed item.
+    Continue,
+}
+
+impl<T: Hash + Eq + Clone> From<TopologicalSort<T>> for Scheduler<T> {
+    fn from(ts: TopologicalSort<T>) -> Scheduler<T> {
+        Scheduler::new(ts)
+    }
+}
+
+impl<T: fmt::Debug + Hash + Eq + Clone> fmt::Debug for Scheduler<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("ts", &self.ts)
+            .field("in_flight", &self.in_flight)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scheduler;
+    use crate::TopologicalSort;
+
+    #[test]
+    fn diamond() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(1, 3);
+        ts.add_dependency(2, 4);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(2, 5);
+        let mut scheduler = Scheduler::new(ts);
+
+        assert_eq!(vec![1], scheduler.take_all());
+        assert!(scheduler.take().is_none());
+        assert!(!scheduler.complete(&2));
+        assert!(scheduler.complete(&1));
+        assert!(!scheduler.complete(&1));
+
+        let mut ready = scheduler.take_all();
+        ready.sort_unstable();
+        assert_eq!(vec![2, 3], ready);
+        assert!(scheduler.complete(&2));
+        assert_eq!(Some(5), scheduler.take());
+        assert!(scheduler.take().is_none());
+        assert_eq!(3, scheduler.len());
+        assert_eq!(2, scheduler.in_flight().len());
+
+        assert!(scheduler.complete(&3));
+        assert!(scheduler.complete(&5));
+        assert_eq!(Some(4), scheduler.take());
+        assert!(scheduler.complete(&4));
+        assert!(scheduler.is_empty());
+        assert!(!scheduler.is_stalled());
+    }
+
+    #[test]
+    fn fail() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(1, 4);
+        ts.add_dependency(5, 3);
+        let mut scheduler = Scheduler::new(ts);
+
+        let mut ready = scheduler.take_all();
+        ready.sort_unstable();
+        assert_eq!(vec![1, 5], ready);
+        assert_eq!(None, scheduler.fail(&2));
+        let mut skipped = scheduler.fail(&1).unwrap();
+        skipped.sort_unstable();
+        assert_eq!(vec![2, 3, 4], skipped);
+        assert_eq!(None, scheduler.take());
+        assert!(scheduler.complete(&5));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn stalled() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 2);
+        let mut scheduler = Scheduler::from(ts);
+        assert_eq!(Some(1), scheduler.take());
+        assert!(!scheduler.is_stalled());
+        assert!(scheduler.complete(&1));
+        assert!(scheduler.is_stalled());
+    }
+}
@@ -0,0 +1,367 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use crate::{CycleError, TopologicalSort};
+
+/// How a simulation chooses which ready item to start next when several are waiting.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Starts the item with the longest chain of durations from its start to the end of all
+    /// items depending on it first.
+    CriticalPathFirst,
+    /// Starts the item with the longest duration first.
+    LongestJobFirst,
+    /// Starts items in the `SortOrder` of the `TopologicalSort`.
+    SortOrder,
+}
+
+/// Where and when an item runs in a [`Schedule`](struct.Schedule.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    /// The worker running the item, from 0 up to but excluding the number of workers.
+    pub worker: usize,
+    /// The time at which the item starts.
+    pub start: u64,
+    /// The time at which the item finishes.
+    pub finish: u64,
+}
+
+/// The result of a simulation, created by [`TopologicalSort::simulate`].
+///
+/// [`TopologicalSort::simulate`]: struct.TopologicalSort.html#method.simulate
+#[derive(Clone, Debug)]
+pub struct Schedule<T> {
+    assignments: HashMap<T, Assignment>,
+    makespan: u64,
+}
+
+impl<T: Hash + Eq + Clone> Schedule<T> {
+    /// Returns the time at which all items are finished.
+    pub fn makespan(&self) -> u64 {
+        self.makespan
+    }
+
+    /// Returns where and when an item runs, or `None` if it was not part of the simulation.
+    pub fn assignment(&self, elt: &T) -> Option<&Assignment> {
+        self.assignments.get(elt)
+    }
+
+    /// Returns where and when all items run.
+    pub fn assignments(&self) -> &HashMap<T, Assignment> {
+        &self.assignments
+    }
+
+    /// Returns the items run by each worker, in the order they start.
+    pub fn by_worker(&self) -> Vec<Vec<T>> {
+        let workers = self.assignments.values().map(|a| a.worker + 1).max();
+        let mut by_worker = vec![Vec::new(); workers.unwrap_or(0)];
+        for (item, a) in &self.assignments {
+            by_worker[a.worker].push((a.start, a.finish, item));
+        }
+        by_worker
+            .into_iter()
+            .map(|mut items| {
+                items.sort_by_key(|&(start, finish, _)| (start, finish));
+                items.into_iter().map(|(_, _, item)| item.clone()).collect()
+            })
+            .collect()
+    }
+}
+
+impl<T: Hash + Eq + Clone> TopologicalSort<T> {
+    /// Simulates running the items on `workers` workers, where `duration` gives how long each
+    /// item takes.
+    ///
+    /// Whenever a worker is free, it starts the ready item chosen by `heuristic`. Returns an
+    /// error if some items cannot be sorted because of cyclic dependencies. A `workers` of 0 is
+    /// treated as 1.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::{Heuristic, TopologicalSort};
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("build", "test");
+    /// ts.add_dependency("build", "docs");
+    /// let durations = |job: &&str| match *job { "build" => 10, "test" => 20, _ => 5 };
+    /// assert_eq!(30, ts.simulate(2, Heuristic::CriticalPathFirst, durations).unwrap().makespan());
+    /// assert_eq!(35, ts.simulate(1, Heuristic::CriticalPathFirst, durations).unwrap().makespan());
+    /// # }
+    /// ```
+    pub fn simulate<D>(
+        &self,
+        workers: usize,
+        heuristic: Heuristic,
+        duration: D,
+    ) -> Result<Schedule<T>, CycleError<T>>
+    where
+        D: FnMut(&T) -> u64,
+    {
+        self.simulate_with_resources(
+            workers,
+            heuristic,
+            duration,
+            &HashMap::<(), usize>::new(),
+            |_| None,
+        )
+    }
+
+    /// Simulates running the items on `workers` workers as `simulate` does, where items also
+    /// need resources with a limited capacity.
+    ///
+    /// `uses` gives the resources each item needs while it runs, each occurrence taking one unit
+    /// of the capacity given by `capacities`. Resources missing from `capacities` are unlimited. An item
+    /// whose needs exceed the capacities only starts once no other item is running. A `workers`
+    /// of 0 is treated as 1.
+    pub fn simulate_with_resources<D, U, I, R>(
+        &self,
+        workers: usize,
+        heuristic: Heuristic,
+        mut duration: D,
+        capacities: &HashMap<R, usize>,
+        mut uses: U,
+    ) -> Result<Schedule<T>, CycleError<T>>
+    where
+        D: FnMut(&T) -> u64,
+        U: FnMut(&T) -> I,
+        I: IntoIterator<Item = R>,
+        R: Hash + Eq,
+    {
+        let order = self.iter().collect::<Vec<_>>();
+        if order.len() < self.len() {
+            return Err(self.cycle_error(&order));
+        }
+
+        // Items are identified by their position in `order` from here on.
+        let index = order
+            .iter()
+            .enumerate()
+            .map(|(i, &item)| (item, i))
+            .collect::<HashMap<_, _>>();
+        let durations = order.iter().map(|&item| duration(item)).collect::<Vec<_>>();
+        // The units of each limited resource every item needs.
+        let needs = order
+            .iter()
+            .map(|&item| {
+                let mut units = HashMap::new();
+                for r in uses(item) {
+                    if capacities.contains_key(&r) {
+                        *units.entry(r).or_insert(0) += 1;
+                    }
+                }
+                units
+            })
+            .collect::<Vec<_>>();
+        let mut priorities = match heuristic {
+            Heuristic::CriticalPathFirst | Heuristic::LongestJobFirst => durations.clone(),
+            Heuristic::SortOrder => vec![0; order.len()],
+        };
+        if heuristic == Heuristic::CriticalPathFirst {
+            for (i, &item) in order.iter().enumerate().rev() {
+                let tail = self.top[item]
+                    .succ
+                    .iter()
+                    .map(|s| priorities[index[s]])
+                    .max();
+                priorities[i] += tail.unwrap_or(0);
+            }
+        }
+
+        let mut num_prec = order
+            .iter()
+            .map(|&item| self.top[item].prec.len())
+            .collect::<Vec<_>>();
+        let mut ready = (0..order.len())
+            .filter(|&i| num_prec[i] == 0)
+            .map(|i| (priorities[i], Reverse(i)))
+            .collect::<BinaryHeap<_>>();
+        let mut free_workers = (0..workers.max(1)).map(Reverse).collect::<BinaryHeap<_>>();
+        let mut used = HashMap::<&R, usize>::new();
+        let mut running = BinaryHeap::new();
+        let mut assignments = HashMap::with_capacity(order.len());
+        let mut now = 0;
+
+        loop {
+            let mut blocked = Vec::new();
+            while !free_workers.is_empty() {
+                let (priority, Reverse(i)) = match ready.pop() {
+                    Some(entry) => entry,
+                    None => break,
+                };
+                let fits = needs[i]
+                    .iter()
+                    .all(|(r, &n)| used.get(r).map_or(0, |&u| u) + n <= capacities[r]);
+                if !fits && !running.is_empty() {
+                    blocked.push((priority, Reverse(i)));
+                    continue;
+                }
+                for (r, &n) in &needs[i] {
+                    *used.entry(r).or_insert(0) += n;
+                }
+                let Reverse(worker) = free_workers.pop().unwrap();
+                let finish = now + durations[i];
+                running.push(Reverse((finish, i, worker)));
+                let _ = assignments.insert(
+                    order[i].clone(),
+                    Assignment {
+                        worker,
+                        start: now,
+                        finish,
+                    },
+                );
+            }
+            ready.extend(blocked);
+
+            // Finish all items finishing at the same time before starting new ones, so that
+            // all items they release are considered.
+            match running.peek() {
+                Some(&Reverse((finish, _, _))) => now = finish,
+                None => break,
+            }
+            while let Some(&Reverse((finish, i, worker))) = running.peek() {
+                if finish != now {
+                    break;
+                }
+                let _ = running.pop();
+                free_workers.push(Reverse(worker));
+                for (r, &n) in &needs[i] {
+                    *used.get_mut(r).unwrap() -= n;
+                }
+                for succ in &self.top[order[i]].succ {
+                    let s = index[succ];
+                    num_prec[s] -= 1;
+                    if num_prec[s] == 0 {
+                        ready.push((priorities[s], Reverse(s)));
+                    }
+                }
+            }
+        }
+
+        Ok(Schedule {
+            assignments,
+            makespan: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heuristic;
+    use crate::{SortOrder, TopologicalSort};
+    use std::collections::HashMap;
+
+    fn jobs() -> (TopologicalSort<&'static str>, HashMap<&'static str, u64>) {
+        let mut ts = TopologicalSort::with_order(SortOrder::insertion());
+        for job in &["lint", "unit", "fmt"] {
+            assert!(ts.insert(*job));
+        }
+        ts.add_dependency("compile", "integration");
+        ts.add_dependency("integration", "deploy");
+        let durations = [
+            ("lint", 3),
+            ("unit", 4),
+            ("fmt", 1),
+            ("compile", 2),
+            ("integration", 5),
+            ("deploy", 2),
+        ];
+        (ts, durations.iter().cloned().collect())
+    }
+
+    #[test]
+    fn heuristics() {
+        let (ts, durations) = jobs();
+        let duration = |job: &&str| durations[job];
+
+        let schedule = ts.simulate(2, Heuristic::SortOrder, duration).unwrap();
+        assert_eq!(13, schedule.makespan());
+        let schedule = ts
+            .simulate(2, Heuristic::LongestJobFirst, duration)
+            .unwrap();
+        assert_eq!(12, schedule.makespan());
+        let schedule = ts
+            .simulate(2, Heuristic::CriticalPathFirst, duration)
+            .unwrap();
+        assert_eq!(9, schedule.makespan());
+        assert_eq!(
+            vec![
+                vec!["compile", "integration", "deploy"],
+                vec!["unit", "lint", "fmt"]
+            ],
+            schedule.by_worker()
+        );
+
+        for job in ts.iter() {
+            let a = schedule.assignment(job).unwrap();
+            assert_eq!(durations[job], a.finish - a.start);
+            for prec in ts.predecessors(job).unwrap() {
+                assert!(schedule.assignment(prec).unwrap().finish <= a.start);
+            }
+        }
+        let schedule = ts.simulate(10, Heuristic::SortOrder, duration).unwrap();
+        assert_eq!(9, schedule.makespan());
+    }
+
+    #[test]
+    fn resources() {
+        let (ts, durations) = jobs();
+        let capacities = [("db", 1)].iter().cloned().collect::<HashMap<_, _>>();
+        let schedule = ts
+            .simulate_with_resources(
+                4,
+                Heuristic::CriticalPathFirst,
+                |job| durations[job],
+                &capacities,
+                |job| match *job {
+                    "unit" | "integration" => vec!["db"],
+                    _ => vec!["cpu"],
+                },
+            )
+            .unwrap();
+        assert_eq!(11, schedule.makespan());
+        let unit = schedule.assignment(&"unit").unwrap();
+        assert_eq!((0, 4), (unit.start, unit.finish));
+        assert_eq!(4, schedule.assignment(&"integration").unwrap().start);
+
+        let mut ts = TopologicalSort::with_order(SortOrder::insertion());
+        assert!(ts.insert("seed"));
+        assert!(ts.insert("migrate"));
+        let capacities = [("db", 2)].iter().cloned().collect::<HashMap<_, _>>();
+        let schedule = ts
+            .simulate_with_resources(
+                2,
+                Heuristic::SortOrder,
+                |_| 3,
+                &capacities,
+                |job| match *job {
+                    "migrate" => vec!["db", "db"],
+                    _ => vec!["db"],
+                },
+            )
+            .unwrap();
+        assert_eq!(6, schedule.makespan());
+    }
+
+    #[test]
+    fn simultaneous_finishes() {
+        let mut ts = TopologicalSort::with_order(SortOrder::insertion());
+        for job in &["a", "b", "x"] {
+            assert!(ts.insert(*job));
+        }
+        ts.add_dependency("b", "h1");
+        ts.add_dependency("b", "h2");
+        let duration = |job: &&str| match *job {
+            "x" => 4,
+            "h1" | "h2" => 10,
+            _ => 5,
+        };
+        let schedule = ts
+            .simulate(2, Heuristic::CriticalPathFirst, duration)
+            .unwrap();
+        assert_eq!(5, schedule.assignment(&"h1").unwrap().start);
+        assert_eq!(5, schedule.assignment(&"h2").unwrap().start);
+        assert_eq!(15, schedule.assignment(&"x").unwrap().start);
+        assert_eq!(19, schedule.makespan());
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::bitset::BitSet;
+use crate::{CycleError, DependencyLink, TopologicalSort};
+
+impl<T: Hash + Eq + Clone> TopologicalSort<T> {
+    /// Returns a copy of the `TopologicalSort` without the dependencies which are implied by
+    /// other dependencies, together with the removed dependencies.
+    ///
+    /// A dependency of `c` on `a` is removed if `c` also depends on `a` indirectly, for example
+    /// through `b` when `c` depends on `b` and `b` depends on `a`. The result has the fewest
+    /// dependencies giving the same order constraints. The removed dependencies are listed in
+    /// topological order of their `prec`, then of their `succ`.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("libc", "libfoo");
+    /// ts.add_dependency("libfoo", "foo-cli");
+    /// ts.add_dependency("libc", "foo-cli");
+    /// let (reduced, removed) = ts.transitive_reduction().unwrap();
+    /// assert!(!reduced.contains_dependency(&"libc", &"foo-cli"));
+    /// assert_eq!(1, removed.len());
+    /// assert_eq!(("libc", "foo-cli"), (removed[0].prec, removed[0].succ));
+    /// # }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn transitive_reduction(
+        &self,
+    ) -> Result<(TopologicalSort<T>, Vec<DependencyLink<T>>), CycleError<T>> {
+        let mut redundant = Vec::new();
+        let (order, _) = self.descendant_sets(|p, s| redundant.push((p, s)))?;
+        redundant.sort_unstable();
+
+        let mut reduced = self.clone();
+        let removed = redundant
+            .into_iter()
+            .map(|(p, s)| {
+                let _ = reduced.remove_dependency(order[p], order[s]);
+                DependencyLink {
+                    prec: order[p].clone(),
+                    succ: order[s].clone(),
+                }
+            })
+            .collect();
+        Ok((reduced, removed))
+    }
+
+    /// Computes the transitive closure of the dependencies, to answer whether an item must
+    /// come before another one in constant time.
+    ///
+    /// The closure takes one bit per pair of items.
+    ///
+    /// Returns an error if some items cannot be sorted because of cyclic dependencies.
+    ///
+    /// ```rust
+    /// # extern crate topological_sort;
+    /// # fn main() {
+    /// use topological_sort::TopologicalSort;
+    /// let mut ts = TopologicalSort::<&str>::new();
+    /// ts.add_dependency("libc", "libfoo");
+    /// ts.add_dependency("libfoo", "foo-cli");
+    /// ts.add_dependency("libbar", "bar-cli");
+    /// let closure = ts.transitive_closure().unwrap();
+    /// assert!(closure.reaches(&"libc", &"foo-cli"));
+    /// assert!(!closure.reaches(&"libc", &"bar-cli"));
+    /// assert_eq!(vec![&"libfoo", &"foo-cli"], closure.descendants(&"libc").collect::<Vec<_>>());
+    /// # }
+    /// ```
+    pub fn transitive_closure(&self) -> Result<TransitiveClosure<T>, CycleError<T>> {
+        let (order, descendants) = self.descendant_sets(|_, _| {})?;
+        let items = order.into_iter().cloned().collect::<Vec<_>>();
+        let index = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.clone(), i))
+            .collect();
+        Ok(TransitiveClosure {
+            items,
+            index,
+            descendants,
+        })
+    }
+
+    /// Returns the items in topological order, with the positions of the descendants of each
+    /// item in that order.
+    ///
+    /// `redundant` is called with the positions of both ends of every dependency which is also
+    /// implied by other dependencies.
+    fn descendant_sets<F>(&self, mut redundant: F) -> Result<(Vec<&T>, Vec<BitSet>), CycleError<T>>
+    where
+        F: FnMut(usize, usize),
+    {
+        let order = self.iter().collect::<Vec<_>>();
+        if order.len() < self.len() {
+            return Err(self.cycle_error(&order));
+        }
+        let index = order
+            .iter()
+            .enumerate()
+            .map(|(i, &item)| (item, i))
+            .collect::<HashMap<_, _>>();
+
+        // Items are visited from last to first, so the descendants of all successors of an
+        // item are known. Visiting the successors of an item from first to last, a successor
+        // which is already reachable through an earlier one is only depended on indirectly.
+        let mut descendants = vec![BitSet::new(order.len()); order.len()];
+        for (i, &item) in order.iter().enumerate().rev() {
+            let mut succ = self.top[item]
+                .succ
+                .iter()
+                .map(|s| index[s])
+                .collect::<Vec<_>>();
+            succ.sort_unstable();
+            let mut reach = BitSet::new(order.len());
+            for s in succ {
+                if reach.contains(s) {
+                    redundant(i, s);
+                    continue;
+                }
+                reach.insert(s);
+                reach.union_with(&descendants[s]);
+            }
+            descendants[i] = reach;
+        }
+        Ok((order, descendants))
+    }
+}
+
+/// The transitive closure of the dependencies of a `TopologicalSort`, created by
+/// [`TopologicalSort::transitive_closure`].
+///
+/// Stores for each item the set of items which must come after it as a bitset, indexed by
+/// position in a topological order.
+///
+/// [`TopologicalSort::transitive_closure`]: struct.TopologicalSort.html#method.transitive_closure
+#[derive(Clone)]
+pub struct TransitiveClosure<T> {
+    items: Vec<T>,
+    index: HashMap<T, usize>,
+    descendants: Vec<BitSet>,
+}
+
+impl<T: Hash + Eq> TransitiveClosure<T> {
+    /// Returns the number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns true if there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns true if the closure contains the item.
+    pub fn contains(&self, elt: &T) -> bool {
+        self.index.contains_key(elt)
+    }
+
+    /// Returns the items in the topological order the closure is indexed by.
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns true if `to` depends on `from`, directly or indirectly.
+    ///
+    /// An item does not reach itself, and unknown items reach nothing.
+    pub fn reaches(&self, from: &T, to: &T) -> bool {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(&from), Some(&to)) => from < to && self.descendants[from].contains(to),
+            _ => false,
+        }
+    }
+
+    /// Returns the items which depend on `elt`, directly or indirectly, in topological order.
+    ///
+    /// Returns nothing if the closure does not contain `elt`.
+    pub fn descendants(&self, elt: &T) -> impl Iterator<Item = &T> + '_ {
+        self.index
+            .get(elt)
+            .into_iter()
+            .flat_map(move |&i| self.descendants[i].iter())
+            .map(move |i| &self.items[i])
+    }
+
+    /// Returns the number of items which depend on `elt`, directly or indirectly, or `None`
+    /// if the closure does not contain `elt`.
+    pub fn descendant_count(&self, elt: &T) -> Option<usize> {
+        self.index.get(elt).map(|&i| self.descendants[i].count())
+    }
+}
+
+impl<T: fmt::Debug + Hash + Eq> fmt::Debug for TransitiveClosure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.items
+                    .iter()
+                    .map(|item| (item, self.descendants(item).collect::<Vec<_>>())),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TopologicalSort;
+    use quickcheck_macros::quickcheck;
+
+    #[test]
+    fn transitive_reduction() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(3, 4);
+        ts.add_dependency(1, 3);
+        ts.add_dependency(1, 4);
+        ts.add_dependency(2, 4);
+        ts.add_dependency(1, 5);
+        ts.add_dependency(5, 4);
+
+        let (reduced, removed) = ts.transitive_reduction().unwrap();
+        let removed = removed
+            .into_iter()
+            .map(|link| (link.prec, link.succ))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(1, 3), (1, 4), (2, 4)], removed);
+        assert_eq!(5, reduced.len());
+        assert!(reduced.contains_dependency(&1, &2));
+        assert!(reduced.contains_dependency(&5, &4));
+        assert!(!reduced.contains_dependency(&1, &4));
+        assert_eq!(Some(2), reduced.in_degree(&4));
+        assert!(ts.contains_dependency(&1, &4));
+
+        ts.add_dependency(4, 1);
+        assert!(ts.transitive_reduction().is_err());
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let mut ts = TopologicalSort::<i32>::new();
+        ts.add_dependency(1, 2);
+        ts.add_dependency(2, 3);
+        ts.add_dependency(1, 4);
+        assert!(ts.insert(5));
+
+        let closure = ts.transitive_closure().unwrap();
+        assert_eq!(5, closure.len());
+        assert!(closure.reaches(&1, &3));
+        assert!(closure.reaches(&2, &3));
+        assert!(!closure.reaches(&3, &1));
+        assert!(!closure.reaches(&1, &1));
+        assert!(!closure.reaches(&4, &3));
+        assert!(!closure.reaches(&1, &6));
+        assert_eq!(Some(3), closure.descendant_count(&1));
+        assert_eq!(Some(0), closure.descendant_count(&5));
+        assert_eq!(None, closure.descendant_count(&6));
+        assert_eq!(0, closure.descendants(&6).count());
+
+        ts.add_dependency(3, 1);
+        assert!(ts.transitive_closure().is_err());
+    }
+
+    #[quickcheck]
+    fn closure_matches_descendants(edges: Vec<(u8, u8)>) {
+        let mut ts = TopologicalSort::<u8>::new();
+        for (a, b) in edges {
+            let _ = ts.try_add_dependency(a % 100, b % 100);
+        }
+        let closure = ts.transitive_closure().unwrap();
+        for from in &ts {
+            let descendants = ts.descendants(from);
+            assert_eq!(descendants, closure.descendants(from).collect());
+            for to in &ts {
+                assert_eq!(descendants.contains(to), closure.reaches(from, to));
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn keeps_reachability(edges: Vec<(u8, u8)>) {
+        let mut ts = TopologicalSort::<u8>::new();
+        for (a, b) in edges {
+            let (a, b) = (a % 16, b % 16);
+            let (prec, succ) = (a.min(b), a.max(b));
+            if prec != succ {
+                ts.add_dependency(prec, succ);
+            }
+        }
+        let (reduced, removed) = ts.transitive_reduction().unwrap();
+        for link in &removed {
+            assert!(reduced.is_reachable(&link.prec, &link.succ));
+        }
+        for item in &ts {
+            assert_eq!(ts.descendants(item), reduced.descendants(item));
+            for succ in reduced.successors(item).unwrap() {
+                let mut without = reduced.clone();
+                assert!(without.remove_dependency(item, succ));
+                assert!(!without.is_reachable(item, succ));
+            }
+        }
+    }
+}